@@ -175,6 +175,42 @@ impl Parse for RuleListenerSpec {
     }
 }
 
+struct RuleDocsSpec {
+    description: Option<Expr>,
+    examples: Option<Expr>,
+}
+
+impl Parse for RuleDocsSpec {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut description: Option<Expr> = Default::default();
+        let mut examples: Option<Expr> = Default::default();
+        let content;
+        braced!(content in input);
+        while !content.is_empty() {
+            let key: Ident = content.parse()?;
+            content.parse::<Token![=>]>()?;
+            match &*key.to_string() {
+                "description" => {
+                    assert!(description.is_none(), "Already saw 'description' key");
+                    description = Some(content.parse()?);
+                }
+                "examples" => {
+                    assert!(examples.is_none(), "Already saw 'examples' key");
+                    examples = Some(content.parse()?);
+                }
+                key => panic!("Unexpected key: '{}'", key),
+            }
+            if !content.is_empty() {
+                content.parse::<Token![,]>()?;
+            }
+        }
+        Ok(Self {
+            description,
+            examples,
+        })
+    }
+}
+
 struct Rule {
     name: Expr,
     fixable: Option<Expr>,
@@ -187,6 +223,10 @@ struct Rule {
     allow_self_conflicting_fixes: Option<Expr>,
     concatenate_adjacent_insert_fixes: Option<Expr>,
     methods: Option<Vec<ImplItem>>,
+    docs_url: Option<Expr>,
+    tags: Vec<Ident>,
+    docs: Option<RuleDocsSpec>,
+    options_schema: Option<Expr>,
 }
 
 impl Rule {
@@ -227,6 +267,10 @@ impl Parse for Rule {
         let mut allow_self_conflicting_fixes: Option<Expr> = Default::default();
         let mut concatenate_adjacent_insert_fixes: Option<Expr> = Default::default();
         let mut methods: Option<Vec<ImplItem>> = Default::default();
+        let mut docs_url: Option<Expr> = Default::default();
+        let mut options_schema: Option<Expr> = Default::default();
+        let mut tags: Option<Vec<Ident>> = Default::default();
+        let mut docs: Option<RuleDocsSpec> = Default::default();
         while !input.is_empty() {
             let key: Ident = input.parse()?;
             #[allow(clippy::collapsible_if)]
@@ -320,6 +364,30 @@ impl Parse for Rule {
                     }
                     methods = Some(methods_present);
                 }
+                "docs_url" => {
+                    assert!(docs_url.is_none(), "Already saw 'docs_url' key");
+                    docs_url = Some(input.parse()?);
+                }
+                "tags" => {
+                    assert!(tags.is_none(), "Already saw 'tags' key");
+                    let tags_content;
+                    bracketed!(tags_content in input);
+                    let tags = tags.get_or_insert_with(|| Default::default());
+                    while !tags_content.is_empty() {
+                        tags.push(tags_content.parse()?);
+                        if !tags_content.is_empty() {
+                            tags_content.parse::<Token![,]>()?;
+                        }
+                    }
+                }
+                "docs" => {
+                    assert!(docs.is_none(), "Already saw 'docs' key");
+                    docs = Some(input.parse()?);
+                }
+                "options_schema" => {
+                    assert!(options_schema.is_none(), "Already saw 'options_schema' key");
+                    options_schema = Some(input.parse()?);
+                }
                 _ => panic!("didn't expect key '{}'", key),
             }
             if !input.is_empty() {
@@ -338,6 +406,10 @@ impl Parse for Rule {
             allow_self_conflicting_fixes,
             concatenate_adjacent_insert_fixes,
             methods,
+            docs_url,
+            tags: tags.unwrap_or_default(),
+            docs,
+            options_schema,
         })
     }
 }
@@ -545,6 +617,37 @@ fn get_rule_rule_impl(
             }
         }
     };
+    let validate_options = match rule.options_type.as_ref() {
+        // options passed to rules that don't take any have always just been
+        // ignored, so the config gets a warning rather than an error
+        None => quote! {
+            fn accepts_options(&self) -> bool {
+                false
+            }
+        },
+        Some(options_type) => {
+            let handle_missing_options = if rule.are_options_required && !is_option_type(options_type) {
+                quote!(Err(#crate_name::RuleOptionsError::new("This rule requires options")))
+            } else {
+                quote!(Ok(()))
+            };
+            quote! {
+                fn validate_options(
+                    &self,
+                    options: Option<&#crate_name::RuleOptions>,
+                ) -> Result<(), #crate_name::RuleOptionsError> {
+                    match options {
+                        None => #handle_missing_options,
+                        Some(options) => #crate_name::validate_rule_options::<#options_type>(options),
+                    }
+                }
+            }
+        }
+    };
+    let options_schema = match rule.options_schema.as_ref() {
+        Some(options_schema) => quote!(Some(#options_schema)),
+        None => quote!(None),
+    };
     let languages = &rule.languages;
     let messages = match rule.messages.as_ref() {
         Some(messages) => {
@@ -567,6 +670,30 @@ fn get_rule_rule_impl(
         Some(concatenate_adjacent_insert_fixes) => quote!(#concatenate_adjacent_insert_fixes),
         None => quote!(false),
     };
+    let docs_url = match rule.docs_url.as_ref() {
+        Some(docs_url) => quote!(Some(#docs_url.into())),
+        None => quote!(None),
+    };
+    let tags = &rule.tags;
+    let docs = match rule.docs.as_ref() {
+        Some(docs) => {
+            let description = match docs.description.as_ref() {
+                Some(description) => quote!(Some(#description.into())),
+                None => quote!(None),
+            };
+            let examples = match docs.examples.as_ref() {
+                Some(examples) => quote!(#examples.into()),
+                None => quote!(Default::default()),
+            };
+            quote! {
+                Some(#crate_name::RuleDocs {
+                    description: #description,
+                    examples: #examples,
+                })
+            }
+        }
+        None => quote!(None),
+    };
     quote! {
         impl #crate_name::Rule for #rule_struct_name {
             fn meta(&self) -> std::sync::Arc<#crate_name::RuleMeta> {
@@ -577,6 +704,10 @@ fn get_rule_rule_impl(
                     messages: #messages,
                     allow_self_conflicting_fixes: #allow_self_conflicting_fixes,
                     concatenate_adjacent_insert_fixes: #concatenate_adjacent_insert_fixes,
+                    docs_url: #docs_url,
+                    tags: vec![#(#crate_name::RuleTag::#tags),*],
+                    docs: #docs,
+                    options_schema: #options_schema,
                 })
             }
 
@@ -598,6 +729,8 @@ fn get_rule_rule_impl(
                     #(#rule_instance_state_field_names: #rule_instance_state_field_initializers),*
                 })
             }
+
+            #validate_options
         }
     }
 }
@@ -14,6 +14,7 @@ struct Violation {
     range: Option<Expr>,
     fix: Option<Expr>,
     data: Option<ExprOrArrowSeparatedKeyValuePairs>,
+    related_locations: Option<Expr>,
 }
 
 impl Parse for Violation {
@@ -24,6 +25,7 @@ impl Parse for Violation {
         let mut range: Option<Expr> = Default::default();
         let mut fix: Option<Expr> = Default::default();
         let mut data: Option<ExprOrArrowSeparatedKeyValuePairs> = Default::default();
+        let mut related_locations: Option<Expr> = Default::default();
 
         while !input.is_empty() {
             let key: Ident = input.parse()?;
@@ -53,6 +55,13 @@ impl Parse for Violation {
                     assert!(data.is_none(), "already saw 'data' key");
                     data = Some(input.parse()?);
                 }
+                "related_locations" => {
+                    assert!(
+                        related_locations.is_none(),
+                        "Already saw 'related_locations'"
+                    );
+                    related_locations = Some(input.parse()?);
+                }
                 _ => panic!("Unexpected key: '{key}'"),
             }
             if !input.is_empty() {
@@ -67,6 +76,7 @@ impl Parse for Violation {
             range,
             fix,
             data,
+            related_locations,
         })
     }
 }
@@ -125,6 +135,11 @@ pub fn violation_with_crate_name(input: TokenStream, crate_name: &str) -> TokenS
         None => quote!(),
     };
 
+    let related_locations = match violation.related_locations.as_ref() {
+        Some(related_locations) => quote!(.related_locations(#related_locations)),
+        None => quote!(),
+    };
+
     quote! {
         #crate_name::ViolationBuilder::default()
             #message
@@ -133,6 +148,7 @@ pub fn violation_with_crate_name(input: TokenStream, crate_name: &str) -> TokenS
             .node(#node)
             #range
             #data
+            #related_locations
             .build().unwrap()
     }
     .into()
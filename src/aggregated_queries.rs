@@ -7,9 +7,15 @@ use once_cell::sync::Lazy;
 use regex::Regex;
 use squalid::regex;
 use tracing::{instrument, trace, trace_span};
-use tree_sitter_grep::{tree_sitter::Query, SupportedLanguageLanguage};
+use tree_sitter_grep::{
+    tree_sitter::{Query, QueryError},
+    SupportedLanguageLanguage,
+};
 
-use crate::rule::{InstantiatedRule, ResolvedMatchBy};
+use crate::{
+    rule::{InstantiatedRule, ResolvedMatchBy},
+    Error,
+};
 
 type RuleIndex = usize;
 type RuleListenerIndex = usize;
@@ -85,6 +91,56 @@ impl AggregatedQueriesPerLanguageBuilder {
 static KIND_ENTER: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^[a-zA-Z_]+$"#).unwrap());
 static KIND_EXIT: Lazy<Regex> = Lazy::new(|| Regex::new(r#"^([a-zA-Z_]+):exit$"#).unwrap());
 
+fn add_non_query_selectors(
+    per_language_builder: &mut AggregatedQueriesPerLanguageBuilder,
+    rule_name: &str,
+    rule_listener_query: &str,
+    rule_index: RuleIndex,
+    rule_listener_index: RuleListenerIndex,
+) -> Result<(), Error> {
+    let invalid_selector = |selector: &str, reason: &'static str| Error::InvalidListenerSelector {
+        rule_name: rule_name.to_owned(),
+        selector: selector.to_owned(),
+        reason,
+    };
+    let mut saw_selector = false;
+    let mut seen_exit_and_enter_kinds: (HashSet<&str>, HashSet<&str>) =
+        (Default::default(), Default::default());
+    for selector in regex!(r#"\s*,\s*"#).split(rule_listener_query.trim()) {
+        if let Some(captures) = KIND_EXIT.captures(selector) {
+            let kind = captures.get(1).unwrap().as_str();
+            if !seen_exit_and_enter_kinds.0.insert(kind) {
+                return Err(invalid_selector(selector, "repeated exit kind"));
+            }
+            per_language_builder
+                .kind_exit_rule_listener_indices
+                .entry(kind.to_owned())
+                .or_default()
+                .push((rule_index, rule_listener_index));
+        } else if KIND_ENTER.is_match(selector) {
+            let kind = selector;
+            if !seen_exit_and_enter_kinds.1.insert(kind) {
+                return Err(invalid_selector(selector, "repeated enter kind"));
+            }
+            per_language_builder
+                .kind_enter_rule_listener_indices
+                .entry(kind.to_owned())
+                .or_default()
+                .push((rule_index, rule_listener_index));
+        } else {
+            return Err(invalid_selector(
+                selector,
+                "expected a node kind or `<kind>:exit`",
+            ));
+        }
+        saw_selector = true;
+    }
+    if !saw_selector {
+        return Err(invalid_selector(rule_listener_query, "expected a selector"));
+    }
+    Ok(())
+}
+
 pub struct AggregatedQueries<'a> {
     pub instantiated_rules: &'a [InstantiatedRule],
     pub per_language: HashMap<SupportedLanguageLanguage, AggregatedQueriesPerLanguage>,
@@ -92,7 +148,7 @@ pub struct AggregatedQueries<'a> {
 
 impl<'a> AggregatedQueries<'a> {
     #[instrument(level = "debug", skip_all)]
-    pub fn new(instantiated_rules: &'a [InstantiatedRule]) -> Self {
+    pub fn new(instantiated_rules: &'a [InstantiatedRule]) -> Result<Self, Error> {
         let mut per_language: HashMap<
             SupportedLanguageLanguage,
             AggregatedQueriesPerLanguageBuilder,
@@ -102,9 +158,9 @@ impl<'a> AggregatedQueries<'a> {
 
         for (rule_index, instantiated_rule) in instantiated_rules.into_iter().enumerate() {
             for &language in &instantiated_rule.meta.languages {
-                let mut has_seen_successful_parsing_for_this_language: HashMap<
+                let mut query_parse_results_for_this_language: HashMap<
                     RuleListenerIndex,
-                    bool,
+                    Result<(), QueryError>,
                 > = Default::default();
                 for &supported_language_language in language.all_supported_language_languages() {
                     let per_language_builder = per_language
@@ -118,64 +174,33 @@ impl<'a> AggregatedQueries<'a> {
                         .listener_queries()
                         .iter()
                         .enumerate()
-                        .filter_map(|(rule_listener_index, rule_listener_query)| {
-                            if !rule_listener_query.query.contains('(') {
-                                let mut saw_selector = false;
-                                let mut seen_exit_and_enter_kinds: (
-                                    HashSet<String>,
-                                    HashSet<&str>,
-                                ) = (Default::default(), Default::default());
-                                for selector in
-                                    regex!(r#"\s*,\s*"#).split(rule_listener_query.query.trim())
-                                {
-                                    if let Some(captures) = KIND_EXIT.captures(selector) {
-                                        let kind = &captures[1];
-                                        if seen_exit_and_enter_kinds.0.contains(kind) {
-                                            panic!("Repeated exit kind");
-                                        }
-                                        seen_exit_and_enter_kinds.0.insert(kind.to_owned());
-                                        per_language_builder
-                                            .kind_exit_rule_listener_indices
-                                            .entry(kind.to_owned())
-                                            .or_default()
-                                            .push((rule_index, rule_listener_index));
-                                    } else if KIND_ENTER.is_match(selector) {
-                                        let kind = selector;
-                                        if seen_exit_and_enter_kinds.1.contains(kind) {
-                                            panic!("Repeated enter kind");
-                                        }
-                                        seen_exit_and_enter_kinds.1.insert(kind);
-                                        per_language_builder
-                                            .kind_enter_rule_listener_indices
-                                            .entry(kind.to_owned())
-                                            .or_default()
-                                            .push((rule_index, rule_listener_index));
-                                    } else {
-                                        panic!("Failed to parse non-query selector");
-                                    }
-                                    saw_selector = true;
-                                }
-                                if !saw_selector {
-                                    panic!("Failed to parse non-query selector");
-                                }
-
-                                return None;
-                            }
-
-                            Some((
-                                rule_listener_index,
-                                rule_listener_query.resolve(supported_language_language.language()),
-                            ))
-                        })
                     {
-                        let has_seen_successful_parsing_for_this_language =
-                            has_seen_successful_parsing_for_this_language
-                                .entry(rule_listener_index)
-                                .or_default();
-                        let Ok(rule_listener_query) = rule_listener_query else {
+                        if !rule_listener_query.query.contains('(') {
+                            add_non_query_selectors(
+                                per_language_builder,
+                                &instantiated_rule.meta.name,
+                                &rule_listener_query.query,
+                                rule_index,
+                                rule_listener_index,
+                            )?;
                             continue;
+                        }
+
+                        let rule_listener_query = match rule_listener_query
+                            .resolve(supported_language_language.language())
+                        {
+                            Ok(rule_listener_query) => {
+                                query_parse_results_for_this_language
+                                    .insert(rule_listener_index, Ok(()));
+                                rule_listener_query
+                            }
+                            Err(query_error) => {
+                                query_parse_results_for_this_language
+                                    .entry(rule_listener_index)
+                                    .or_insert(Err(query_error));
+                                continue;
+                            }
                         };
-                        *has_seen_successful_parsing_for_this_language = true;
 
                         let capture_name_if_per_capture: CaptureNameIfPerCapture =
                             match &rule_listener_query.match_by {
@@ -198,18 +223,33 @@ impl<'a> AggregatedQueries<'a> {
                         per_language_builder.query_text.push_str("\n\n");
                     }
                 }
-                if has_seen_successful_parsing_for_this_language
-                    .iter()
-                    .any(|(_, successfully_parsed)| !*successfully_parsed)
+                // a listener query only has to parse for one of the grammars of the
+                // supported language
+                if let Some((rule_listener_index, query_error)) =
+                    query_parse_results_for_this_language
+                        .into_iter()
+                        .filter_map(|(rule_listener_index, result)| {
+                            result
+                                .err()
+                                .map(|query_error| (rule_listener_index, query_error))
+                        })
+                        .min_by_key(|(rule_listener_index, _)| *rule_listener_index)
                 {
-                    panic!("Found a listener query that couldn't be parsed for any language grammar of the supported language");
+                    return Err(Error::Query {
+                        rule_name: instantiated_rule.meta.name.clone(),
+                        query_text: instantiated_rule.rule_instance.listener_queries()
+                            [rule_listener_index]
+                            .query
+                            .clone(),
+                        source: query_error,
+                    });
                 }
             }
         }
 
         span.exit();
 
-        Self {
+        Ok(Self {
             instantiated_rules,
             per_language: {
                 let span = trace_span!("aggregating per-language queries").entered();
@@ -227,7 +267,7 @@ impl<'a> AggregatedQueries<'a> {
 
                 per_language
             },
-        }
+        })
     }
 
     pub fn is_wildcard_listener(
@@ -0,0 +1,250 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    env, fmt, fs, iter,
+    path::{Component, Path, PathBuf},
+};
+
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{Config, Error, ViolationWithContext};
+
+#[derive(Debug, Default, Deserialize, Serialize)]
+pub(crate) struct Baseline {
+    entries: Vec<BaselineEntry>,
+}
+
+// Keyed by the reported node's text rather than its location so that
+// unrelated edits elsewhere in the file don't invalidate entries
+#[derive(Clone, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+struct BaselineKey {
+    rule: String,
+    path: String,
+    message: String,
+    fingerprint: String,
+}
+
+impl BaselineKey {
+    fn for_violation(
+        violation: &ViolationWithContext,
+        config: &Config,
+        baseline_directory: &BaselineDirectory,
+    ) -> Self {
+        Self {
+            rule: violation.plugin_prefixed_rule_name(config),
+            path: baseline_directory.get_relative_path(&violation.path),
+            message: violation.message().into_owned(),
+            fingerprint: format!("{:016x}", violation.fingerprint),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub(crate) struct BaselineEntry {
+    #[serde(flatten)]
+    key: BaselineKey,
+    count: usize,
+}
+
+impl fmt::Display for BaselineEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{:?} {} {} (fingerprint {})",
+            self.key.path, self.key.message, self.key.rule, self.key.fingerprint
+        )?;
+        if self.count > 1 {
+            write!(f, " (x{})", self.count)?;
+        }
+        Ok(())
+    }
+}
+
+// Baseline paths are relative to the baseline file's directory so that the
+// baseline applies no matter which directory it gets run from
+pub(crate) struct BaselineDirectory {
+    current_directory: PathBuf,
+    path: PathBuf,
+}
+
+impl BaselineDirectory {
+    pub(crate) fn new(baseline_path: &Path) -> Result<Self, Error> {
+        let current_directory = env::current_dir().map_err(Error::CurrentDirectory)?;
+        let path = normalize_absolute(&current_directory.join(baseline_path))
+            .parent()
+            .unwrap()
+            .to_owned();
+        Ok(Self {
+            current_directory,
+            path,
+        })
+    }
+
+    // Always `/`-separated since baseline files get committed
+    fn get_relative_path(&self, path: &Path) -> String {
+        let path = normalize_absolute(&self.current_directory.join(path));
+        let num_common_components = path
+            .components()
+            .zip(self.path.components())
+            .take_while(|(a, b)| a == b)
+            .count();
+        iter::repeat(Cow::Borrowed(".."))
+            .take(self.path.components().count() - num_common_components)
+            .chain(
+                path.components()
+                    .skip(num_common_components)
+                    .map(|component| component.as_os_str().to_string_lossy()),
+            )
+            .join("/")
+    }
+}
+
+fn normalize_absolute(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+impl Baseline {
+    pub(crate) fn from_violations(
+        violations: &[ViolationWithContext],
+        config: &Config,
+        baseline_directory: &BaselineDirectory,
+    ) -> Self {
+        let mut counts: BTreeMap<BaselineKey, usize> = Default::default();
+        for violation in violations {
+            if violation.is_internal_error {
+                continue;
+            }
+            *counts
+                .entry(BaselineKey::for_violation(
+                    violation,
+                    config,
+                    baseline_directory,
+                ))
+                .or_default() += 1;
+        }
+        Self {
+            entries: counts
+                .into_iter()
+                .map(|(key, count)| BaselineEntry { key, count })
+                .collect(),
+        }
+    }
+
+    fn load(path: &Path) -> Result<Self, Error> {
+        let contents = fs::read_to_string(path).map_err(|source| Error::BaselineFileRead {
+            path: path.to_owned(),
+            source,
+        })?;
+        serde_json::from_str(&contents).map_err(|source| Error::BaselineFileParse {
+            path: path.to_owned(),
+            source,
+        })
+    }
+
+    fn write(&self, path: &Path) -> Result<(), Error> {
+        let mut contents = serde_json::to_string_pretty(self).unwrap();
+        contents.push('\n');
+        fs::write(path, contents).map_err(|source| Error::BaselineFileWrite {
+            path: path.to_owned(),
+            source,
+        })
+    }
+
+    // Returns the violations that aren't covered by the baseline along with
+    // the baseline entries that no longer (fully) occur
+    pub(crate) fn filter(
+        &self,
+        violations: Vec<ViolationWithContext>,
+        config: &Config,
+        baseline_directory: &BaselineDirectory,
+    ) -> (Vec<ViolationWithContext>, Vec<BaselineEntry>) {
+        let mut remaining_counts: HashMap<&BaselineKey, usize> = self
+            .entries
+            .iter()
+            .map(|entry| (&entry.key, entry.count))
+            .collect();
+        let new_violations = violations
+            .into_iter()
+            .filter(|violation| {
+                if violation.is_internal_error {
+                    return true;
+                }
+                match remaining_counts.get_mut(&BaselineKey::for_violation(
+                    violation,
+                    config,
+                    baseline_directory,
+                )) {
+                    Some(count) if *count > 0 => {
+                        *count -= 1;
+                        false
+                    }
+                    _ => true,
+                }
+            })
+            .collect();
+        let stale_entries = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let count = remaining_counts[&entry.key];
+                (count > 0).then(|| BaselineEntry {
+                    key: entry.key.clone(),
+                    count,
+                })
+            })
+            .collect();
+        (new_violations, stale_entries)
+    }
+}
+
+// With --write-baseline, (re)writes the baseline file from the given
+// violations first, so only internal errors make it through
+#[instrument(level = "debug", skip_all)]
+pub(crate) fn apply_baseline(
+    config: &Config,
+    violations: Vec<ViolationWithContext>,
+) -> Result<(Vec<ViolationWithContext>, Vec<BaselineEntry>), Error> {
+    let Some(baseline_path) = config.baseline_path.as_ref() else {
+        return Ok((violations, Default::default()));
+    };
+    let baseline_directory = BaselineDirectory::new(baseline_path)?;
+    let baseline = if config.write_baseline {
+        let baseline = Baseline::from_violations(&violations, config, &baseline_directory);
+        baseline.write(baseline_path)?;
+        baseline
+    } else {
+        Baseline::load(baseline_path)?
+    };
+    Ok(baseline.filter(violations, config, &baseline_directory))
+}
+
+// FNV-1a, since the fingerprints get committed and so need to be stable
+// across Rust versions (unlike `DefaultHasher`)
+pub(crate) fn get_fingerprint(node_text: &str) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const PRIME: u64 = 0x100000001b3;
+
+    let mut hash = OFFSET_BASIS;
+    // so that reindenting or reflowing doesn't change the fingerprint
+    for (index, word) in node_text.split_whitespace().enumerate() {
+        if index > 0 {
+            hash = (hash ^ u64::from(b' ')).wrapping_mul(PRIME);
+        }
+        for byte in word.bytes() {
+            hash = (hash ^ u64::from(byte)).wrapping_mul(PRIME);
+        }
+    }
+    hash
+}
@@ -10,12 +10,17 @@ use itertools::Itertools;
 use quote::{format_ident, quote};
 use tracing::{debug, debug_span, instrument};
 
+#[cfg(unix)]
+use crate::daemon::{can_forward_to_daemon, forward_to_daemon, get_daemon_socket_path};
 use crate::{
-    config::{find_config_file, load_config_file, ParsedConfigFile, TreeSitterLintDependencySpec},
-    Args,
+    config::{
+        find_config_file, load_config_file, ParsedConfigFile, PluginSpecValue,
+        TreeSitterLintDependencySpec,
+    },
+    print_error, Args, Error, ExitStatus,
 };
 
-const PER_PROJECT_DIRECTORY_NAME: &str = ".tree-sitter-lint";
+pub(crate) const PER_PROJECT_DIRECTORY_NAME: &str = ".tree-sitter-lint";
 
 const LOCAL_BINARY_PROJECT_NAME: &str = "tree-sitter-lint-local";
 
@@ -23,12 +28,6 @@ const LOCAL_BINARY_LSP_NAME: &str = "tree-sitter-lint-local-lsp";
 
 #[instrument]
 pub fn bootstrap_cli() {
-    let config_file_path = find_config_file();
-    let project_directory = config_file_path.parent().unwrap();
-    let per_project_directory = project_directory.join(PER_PROJECT_DIRECTORY_NAME);
-    let local_binary_project_directory = per_project_directory.join(LOCAL_BINARY_PROJECT_NAME);
-    let path_to_local_release_binary =
-        local_binary_project_directory.join(format!("target/release/{LOCAL_BINARY_PROJECT_NAME}"));
     let command_line_args = env::args_os().collect::<Vec<_>>();
 
     let span = debug_span!("parse args").entered();
@@ -37,15 +36,56 @@ pub fn bootstrap_cli() {
 
     span.exit();
 
-    if should_regenerate_local_binary(&config_file_path, &path_to_local_release_binary, &args) {
-        regenerate_local_binary(&local_binary_project_directory, &Path::new("..").join(".."));
+    let config_file_path = args
+        .config
+        .clone()
+        .map_or_else(find_config_file, Ok)
+        .unwrap_or_else(|error| exit_with_error(&error));
+    let project_directory = config_file_path.parent().unwrap();
+    let per_project_directory = project_directory.join(PER_PROJECT_DIRECTORY_NAME);
+    let local_binary_project_directory = per_project_directory.join(LOCAL_BINARY_PROJECT_NAME);
+    let path_to_local_release_binary =
+        local_binary_project_directory.join(format!("target/release/{LOCAL_BINARY_PROJECT_NAME}"));
+
+    let should_regenerate =
+        should_regenerate_local_binary(&config_file_path, &path_to_local_release_binary, &args);
+    #[cfg(unix)]
+    if !should_regenerate && can_forward_to_daemon(&args) {
+        if let Some(exit_code) = command_line_args
+            .iter()
+            .skip(1)
+            .map(|arg| arg.to_str().map(ToOwned::to_owned))
+            .collect::<Option<Vec<_>>>()
+            .and_then(|args| forward_to_daemon(&get_daemon_socket_path(&config_file_path), args))
+        {
+            process::exit(exit_code);
+        }
+    }
+    if should_regenerate {
+        regenerate_local_binary(
+            &local_binary_project_directory,
+            &Path::new("..").join(".."),
+            &config_file_path,
+        );
     }
     let mut handle = Command::new(path_to_local_release_binary)
         .args(command_line_args.into_iter().skip(1))
         .envs(env::vars())
         .spawn()
         .unwrap();
-    process::exit(handle.wait().unwrap().code().unwrap_or(1));
+    process::exit(
+        handle
+            .wait()
+            .unwrap()
+            .code()
+            // killed by a signal
+            .unwrap_or_else(|| ExitStatus::InternalError.code()),
+    );
+}
+
+fn exit_with_error(error: &Error) -> ! {
+    print_error(error);
+    process::exit(ExitStatus::SetupError.code());
 }
 
 #[instrument]
@@ -81,9 +121,11 @@ const LOCAL_RULES_DIR_NAME: &str = "local_rules";
 fn regenerate_local_binary(
     local_binary_project_directory: &Path,
     relative_path_from_local_binary_project_directory_to_project_directory: &Path,
+    config_file_path: &Path,
 ) {
     eprintln!("Config changed, regenerating local binary");
-    let parsed_config_file = load_config_file();
+    let parsed_config_file =
+        load_config_file(Some(config_file_path)).unwrap_or_else(|error| exit_with_error(&error));
     let local_binary_project_src_directory = local_binary_project_directory.join("src");
     let local_binary_project_cargo_toml_path = local_binary_project_directory.join("Cargo.toml");
     if local_binary_project_directory.is_dir() {
@@ -107,7 +149,8 @@ fn regenerate_local_binary(
             .content
             .tree_sitter_lint_dependency
             .as_ref(),
-    );
+    )
+    .unwrap_or_else(|error| exit_with_error(&error));
     fs::write(local_binary_project_cargo_toml_path, cargo_toml_contents)
         .expect("Couldn't write local binary project Cargo.toml");
 
@@ -155,6 +198,10 @@ fn regenerate_local_binary(
 }
 
 fn release_build_local_binary(local_binary_project_directory: &Path) {
+    // Offline builds of non-path plugins are up to cargo: it picks up source
+    // replacement (vendored sources, local registry mirrors) from a
+    // .cargo/config.toml in the project directory or above, and respects
+    // CARGO_NET_OFFLINE
     let output = Command::new("cargo")
         .args(["build", "--release", "--bin", LOCAL_BINARY_PROJECT_NAME])
         .current_dir(local_binary_project_directory)
@@ -170,7 +217,7 @@ fn get_local_binary_cargo_toml_contents(
     has_local_rules: bool,
     relative_path_from_local_binary_project_directory_to_project_directory: &Path,
     tree_sitter_lint_dependency: Option<&TreeSitterLintDependencySpec>,
-) -> String {
+) -> Result<String, Error> {
     let mut contents = String::new();
     contents.push_str("[package]\n");
     contents.push_str(&format!("name = \"{}\"\n", LOCAL_BINARY_PROJECT_NAME));
@@ -178,14 +225,17 @@ fn get_local_binary_cargo_toml_contents(
     contents.push_str("edition = \"2021\"\n\n");
     contents.push_str("[dependencies]\n");
     contents.push_str(&format!(
-        "tree-sitter-lint = {{ path = \"{}\" }}\n",
-        tree_sitter_lint_dependency.map_or("../..".to_owned(), |tree_sitter_lint_dependency| {
-            relative_path_from_local_binary_project_directory_to_project_directory
-                .join(&tree_sitter_lint_dependency.path)
-                .to_str()
-                .unwrap()
-                .to_owned()
-        }),
+        "tree-sitter-lint = {{ path = {} }}\n",
+        to_toml_string(&tree_sitter_lint_dependency.map_or(
+            "../..".to_owned(),
+            |tree_sitter_lint_dependency| {
+                relative_path_from_local_binary_project_directory_to_project_directory
+                    .join(&tree_sitter_lint_dependency.path)
+                    .to_str()
+                    .unwrap()
+                    .to_owned()
+            }
+        )),
     ));
     if has_local_rules {
         contents.push_str(&format!(
@@ -193,23 +243,19 @@ fn get_local_binary_cargo_toml_contents(
             LOCAL_RULES_DIR_NAME
         ));
     }
-    for (plugin, plugin_spec) in &parsed_config_file.content.plugins {
-        let path = plugin_spec
-            .path
-            .as_ref()
-            .expect("Currently only handling local path plugin dependencies");
-        let path =
-            relative_path_from_local_binary_project_directory_to_project_directory.join(path);
-        contents.push_str(&format!(
-            "{} = {{ path = \"{}\" }}\n",
-            get_plugin_crate_name(plugin),
-            path.to_str()
-                .expect("Couldn't convert plugin path to string")
-        ));
+    for (plugin, plugin_spec) in parsed_config_file
+        .content
+        .plugins
+        .iter()
+        .sorted_by(|(a, _), (b, _)| a.cmp(b))
+    {
+        contents.push_str(&get_plugin_dependency(
+            plugin,
+            plugin_spec,
+            relative_path_from_local_binary_project_directory_to_project_directory,
+        )?);
     }
     contents.push_str("tracing = \"0.1.37\"\n");
-    contents.push_str("tracing-subscriber = { version = \"0.3.17\", features = [\"std\", \"fmt\", \"env-filter\"] }\n");
-    contents.push_str("tracing-chrome = \"0.7.1\"\n");
 
     contents.push_str("\n[patch.crates-io]\n");
     contents.push_str("tree-sitter = { git = \"https://github.com/tree-sitter/tree-sitter\", rev = \"c16b90d\" }\n\n");
@@ -218,39 +264,105 @@ fn get_local_binary_cargo_toml_contents(
     contents.push_str("[[bin]]\n");
     contents.push_str(&format!("name = \"{}\"\n\n", LOCAL_BINARY_LSP_NAME));
     contents.push_str("[workspace]\n");
-    contents
+    Ok(contents)
+}
+
+pub(crate) fn get_plugin_dependency(
+    plugin_name: &str,
+    plugin_spec: &PluginSpecValue,
+    relative_path_from_local_binary_project_directory_to_project_directory: &Path,
+) -> Result<String, Error> {
+    let invalid_plugin_spec = |reason| Error::InvalidPluginSpec {
+        plugin_name: plugin_name.to_owned(),
+        reason,
+    };
+    if plugin_spec.path.is_none() && plugin_spec.version.is_none() && plugin_spec.git.is_none() {
+        return Err(invalid_plugin_spec(
+            "expected one of `path`, `version` or `git`",
+        ));
+    }
+    if plugin_spec.path.is_some() && plugin_spec.git.is_some() {
+        return Err(invalid_plugin_spec("can't specify both `path` and `git`"));
+    }
+    let git_refs = [
+        ("rev", plugin_spec.rev.as_ref()),
+        ("tag", plugin_spec.tag.as_ref()),
+        ("branch", plugin_spec.branch.as_ref()),
+    ]
+    .into_iter()
+    .filter_map(|(key, value)| value.map(|value| (key, value)))
+    .collect::<Vec<_>>();
+    if !git_refs.is_empty() && plugin_spec.git.is_none() {
+        return Err(invalid_plugin_spec(
+            "`rev`, `tag` and `branch` are only allowed alongside `git`",
+        ));
+    }
+    if git_refs.len() > 1 {
+        return Err(invalid_plugin_spec(
+            "only one of `rev`, `tag` or `branch` is allowed",
+        ));
+    }
+
+    let mut fields: Vec<(&str, String)> = Default::default();
+    if let Some(package) = plugin_spec.package.as_ref() {
+        fields.push(("package", package.clone()));
+    }
+    if let Some(version) = plugin_spec.version.as_ref() {
+        fields.push(("version", version.clone()));
+    }
+    if let Some(path) = plugin_spec.path.as_ref() {
+        fields.push((
+            "path",
+            relative_path_from_local_binary_project_directory_to_project_directory
+                .join(path)
+                .to_str()
+                .expect("Couldn't convert plugin path to string")
+                .to_owned(),
+        ));
+    }
+    if let Some(git) = plugin_spec.git.as_ref() {
+        fields.push(("git", git.clone()));
+    }
+    fields.extend(
+        git_refs
+            .into_iter()
+            .map(|(key, value)| (key, value.clone())),
+    );
+    Ok(format!(
+        "{} = {{ {} }}\n",
+        get_plugin_crate_name(plugin_name),
+        fields
+            .into_iter()
+            .map(|(key, value)| format!("{key} = {}", to_toml_string(&value)))
+            .join(", ")
+    ))
+}
+
+// A TOML basic string, eg for Windows paths or git URLs containing quotes
+fn to_toml_string(value: &str) -> String {
+    let mut toml_string = String::with_capacity(value.len() + 2);
+    toml_string.push('"');
+    for ch in value.chars() {
+        match ch {
+            '"' => toml_string.push_str("\\\""),
+            '\\' => toml_string.push_str("\\\\"),
+            ch if ch.is_control() => toml_string.push_str(&format!("\\u{:04X}", ch as u32)),
+            ch => toml_string.push(ch),
+        }
+    }
+    toml_string.push('"');
+    toml_string
 }
 
 fn get_src_bin_tree_sitter_lint_local_rs_contents(local_binary_crate_name: &str) -> String {
     let local_binary_crate_name = format_ident!("{}", local_binary_crate_name);
     quote! {
-        use std::{env, process};
-
-        use tracing_chrome::ChromeLayerBuilder;
-        use tracing_subscriber::prelude::*;
-        use tree_sitter_lint::squalid::NonEmpty;
-        use tree_sitter_lint::ExitStatus;
+        use std::process;
 
         fn main() {
-            let guard = if env::var("TRACE_CHROME").ok().is_non_empty() {
-                let (chrome_layer, guard) = ChromeLayerBuilder::new().include_args(true).build();
-                tracing_subscriber::registry().with(chrome_layer).init();
-                Some(guard)
-            } else {
-                tracing_subscriber::fmt::init();
-                None
-            };
-
             let exit_status = #local_binary_crate_name::run_and_output();
 
-            if let Some(guard) = guard {
-                drop(guard);
-            }
-
-            process::exit(match exit_status {
-                ExitStatus::Ok => 0,
-                ExitStatus::FoundViolations => 1,
-            });
+            process::exit(exit_status.code());
         }
     }
     .to_string()
@@ -259,48 +371,13 @@ fn get_src_bin_tree_sitter_lint_local_rs_contents(local_binary_crate_name: &str)
 fn get_src_bin_tree_sitter_lint_local_lsp_rs_contents(local_binary_crate_name: &str) -> String {
     let local_binary_crate_name = format_ident!("{}", local_binary_crate_name);
     quote! {
-        use std::{env, fs::File, path::PathBuf, sync::mpsc, thread};
-
-        use tracing_chrome::ChromeLayerBuilder;
-        use tracing_subscriber::{prelude::*, EnvFilter};
-        use tree_sitter_lint::{tokio, squalid::NonEmpty};
+        use tree_sitter_lint::tokio;
 
         #[tokio::main]
         async fn main() {
-            let guard = if env::var("TRACE_CHROME").ok().is_non_empty() {
-                let (chrome_layer, guard) = ChromeLayerBuilder::new().include_args(true).file("/Users/jrosse/prj/hello-world/trace.json").build();
-                tracing_subscriber::registry().with(chrome_layer).init();
-                Some(guard)
-            } else if let Some(tracing_log_file_path) = env::var("TRACING_LOG_PATH").ok().non_empty() {
-                let out_log = std::fs::OpenOptions::new()
-                    .write(true)
-                    .append(true)
-                    .create(true)
-                    .open(tracing_log_file_path)
-                    .unwrap();
-                tracing_subscriber::fmt()
-                    .with_env_filter(EnvFilter::from_default_env())
-                    .with_writer(out_log)
-                    .init();
-                None
-            } else {
-                None
-            };
+            let start_new_trace_sender = tree_sitter_lint::init_lsp_tracing();
 
-            // FlushGuard isn't Sync so can't just pass it
-            // around directly I guess (for being able to end/start
-            // new traces)
-            let sender = guard.map(|guard| {
-                let (sender, receiver) = mpsc::channel::<PathBuf>();
-                thread::spawn(move || {
-                    for message in receiver {
-                        guard.start_new(Some(Box::new(File::create(message).unwrap())));
-                    }
-                });
-                sender
-            });
-
-            #local_binary_crate_name::run_lsp(sender).await;
+            #local_binary_crate_name::run_lsp(Some(start_new_trace_sender)).await;
         }
     }
     .to_string()
@@ -359,16 +436,46 @@ fn get_src_lib_rs_contents(parsed_config_file: &ParsedConfigFile, has_local_rule
         use tree_sitter_lint::{
             better_any::Tid,
             clap::Parser, tree_sitter::Tree, tree_sitter_grep::{RopeOrSlice, SupportedLanguage},
-            Args, Config, FileRunContext, FromFileRunContextInstanceProvider,
+            Args, Command, Config, FileRunContext, FromFileRunContextInstanceProvider,
             FromFileRunContextInstanceProviderFactory, FromFileRunContextProvidedTypes,
             FromFileRunContextProvidedTypesOnceLockStorage, MutRopeOrSlice, Plugin, Rule,
             lsp::{ArgsOrConfig, LocalLinter, self}, FixingForSliceRunStatus,
-            FixingForSliceRunContext, PerConfigContext, SliceRunStatus, ExitStatus
+            FixingForSliceRunContext, PerConfigContext, SliceRunStatus, ExitStatus,
+            ViolationWithContext, Error
         };
 
         pub fn run_and_output() -> ExitStatus {
+            let args = Args::parse();
+            let _guard = match tree_sitter_lint::init_cli_tracing(args.trace.as_deref()) {
+                Ok(guard) => guard,
+                Err(error) => {
+                    tree_sitter_lint::print_error(&error);
+                    return ExitStatus::SetupError;
+                }
+            };
+            if matches!(args.command, Some(Command::Daemon)) {
+                return tree_sitter_lint::run_daemon(
+                    args,
+                    args_to_config,
+                    &FromFileRunContextInstanceProviderFactoryLocal,
+                );
+            }
+            if args.watch {
+                return tree_sitter_lint::run_watch(
+                    args,
+                    args_to_config,
+                    &FromFileRunContextInstanceProviderFactoryLocal,
+                );
+            }
+            let config = match args_to_config(args) {
+                Ok(config) => config,
+                Err(error) => {
+                    tree_sitter_lint::print_error(&error);
+                    return ExitStatus::SetupError;
+                }
+            };
             tree_sitter_lint::run_and_output(
-                args_to_config(Args::parse()),
+                config,
                 &FromFileRunContextInstanceProviderFactoryLocal,
             )
         }
@@ -380,7 +487,7 @@ fn get_src_lib_rs_contents(parsed_config_file: &ParsedConfigFile, has_local_rule
             config: &Config,
             language: SupportedLanguage,
             per_config_context: Option<&PerConfigContext>,
-        ) -> SliceRunStatus {
+        ) -> Result<SliceRunStatus, Error> {
             let path = path.as_ref();
             tree_sitter_lint::run_for_slice(
                 file_contents,
@@ -393,6 +500,17 @@ fn get_src_lib_rs_contents(parsed_config_file: &ParsedConfigFile, has_local_rule
             )
         }
 
+        pub fn run_with_per_file_callback(
+            args: Args,
+            on_file_violations: impl Fn(&Path, Vec<ViolationWithContext>) + Sync,
+        ) -> Result<(), Error> {
+            tree_sitter_lint::run_with_per_file_callback(
+                &args_to_config(args)?,
+                &FromFileRunContextInstanceProviderFactoryLocal,
+                on_file_violations,
+            )
+        }
+
         pub fn run_fixing_for_slice<'a>(
             file_contents: impl Into<MutRopeOrSlice<'a>>,
             tree: Option<Tree>,
@@ -400,13 +518,13 @@ fn get_src_lib_rs_contents(parsed_config_file: &ParsedConfigFile, has_local_rule
             args: Args,
             language: SupportedLanguage,
             context: FixingForSliceRunContext,
-        ) -> FixingForSliceRunStatus {
+        ) -> Result<FixingForSliceRunStatus, Error> {
             let path = path.as_ref();
             tree_sitter_lint::run_fixing_for_slice(
                 file_contents,
                 tree,
                 path,
-                args_to_config(args),
+                args_to_config(args)?,
                 language.supported_language_language(Some(path)),
                 &FromFileRunContextInstanceProviderFactoryLocal,
                 context,
@@ -424,16 +542,16 @@ fn get_src_lib_rs_contents(parsed_config_file: &ParsedConfigFile, has_local_rule
                 args_or_config: ArgsOrConfig,
                 language: SupportedLanguage,
                 per_config_context: Option<&PerConfigContext>,
-            ) -> (SliceRunStatus, Option<Config>) {
+            ) -> Result<(SliceRunStatus, Option<Config>), Error> {
                 let passed_config: Option<&'_ Config> = match args_or_config {
                     ArgsOrConfig::Config(config) => Some(config),
                     ArgsOrConfig::Args(_) => None,
                 };
                 let newly_created_config: Option<Config> = match args_or_config {
-                    ArgsOrConfig::Args(args) => Some(args_to_config(args)),
+                    ArgsOrConfig::Args(args) => Some(args_to_config(args)?),
                     ArgsOrConfig::Config(_) => None,
                 };
-                (
+                Ok((
                     run_for_slice(
                         file_contents,
                         tree,
@@ -441,9 +559,9 @@ fn get_src_lib_rs_contents(parsed_config_file: &ParsedConfigFile, has_local_rule
                         passed_config.unwrap_or_else(|| newly_created_config.as_ref().unwrap()),
                         language,
                         per_config_context,
-                    ),
+                    )?,
                     newly_created_config
-                )
+                ))
             }
 
             fn run_fixing_for_slice<'a>(
@@ -454,16 +572,24 @@ fn get_src_lib_rs_contents(parsed_config_file: &ParsedConfigFile, has_local_rule
                 args: Args,
                 language: SupportedLanguage,
                 context: FixingForSliceRunContext,
-            ) -> FixingForSliceRunStatus {
+            ) -> Result<FixingForSliceRunStatus, Error> {
                 run_fixing_for_slice(file_contents, tree, path, args, language, context)
             }
+
+            fn run_with_per_file_callback(
+                &self,
+                args: Args,
+                on_file_violations: impl Fn(&Path, Vec<ViolationWithContext>) + Sync,
+            ) -> Result<(), Error> {
+                run_with_per_file_callback(args, on_file_violations)
+            }
         }
 
         pub async fn run_lsp(start_new_trace_sender: Option<mpsc::Sender<PathBuf>>) {
             lsp::run(LocalLinterConcrete, start_new_trace_sender).await;
         }
 
-        fn args_to_config(args: Args) -> Config {
+        fn args_to_config(args: Args) -> Result<Config, Error> {
             args.load_config_file_and_into_config(all_plugins(), all_standalone_rules())
         }
 
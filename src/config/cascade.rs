@@ -0,0 +1,147 @@
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use dashmap::DashMap;
+use tracing::instrument;
+
+use super::{
+    find_config_file_from, load_config_file_cascade, resolve_rule_configurations, Config,
+    RuleConfiguration, RuleConfigurationOrigin,
+};
+use crate::{
+    aggregated_queries::AggregatedQueries, Error, PerConfigContext, PerConfigContextTryBuilder,
+};
+
+pub(crate) struct CascadedConfig {
+    pub config_file_path: PathBuf,
+    pub rule_configurations: Vec<RuleConfiguration>,
+    pub rule_configuration_origins: HashMap<String, RuleConfigurationOrigin>,
+    pub per_config_context: PerConfigContext,
+    // of each config file in the cascade, for noticing when it's stale
+    #[cfg_attr(not(unix), allow(dead_code))]
+    modified_timestamps: Vec<(PathBuf, Option<SystemTime>)>,
+}
+
+#[derive(Default)]
+pub(crate) struct CascadedConfigs {
+    // None means the top-level config applies
+    nearest_config_file_by_directory: DashMap<PathBuf, Option<PathBuf>>,
+    by_config_file_path: DashMap<PathBuf, Arc<CascadedConfig>>,
+}
+
+#[cfg(unix)]
+impl CascadedConfigs {
+    // For the daemon to reuse across runs. Finding the nearest config
+    // file is cheap so that just starts over (in case any got added or
+    // removed), but the instantiated rules are kept unless one of their config
+    // files changed
+    pub(crate) fn clear_stale(&self) {
+        self.nearest_config_file_by_directory.clear();
+        self.by_config_file_path.retain(|_, cascaded_config| {
+            cascaded_config
+                .modified_timestamps
+                .iter()
+                .all(|(path, modified_timestamp)| {
+                    get_modified_timestamp(path) == *modified_timestamp
+                })
+        });
+    }
+}
+
+fn get_modified_timestamp(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).ok()?.modified().ok()
+}
+
+impl Config {
+    #[cfg(unix)]
+    pub(crate) fn cascaded_configs(&self) -> &Arc<CascadedConfigs> {
+        &self.cascaded_configs
+    }
+
+    #[cfg(unix)]
+    pub(crate) fn set_cascaded_configs(&mut self, cascaded_configs: Arc<CascadedConfigs>) {
+        self.cascaded_configs = cascaded_configs;
+    }
+
+    // Returns None for files that the top-level config applies to
+    #[instrument(level = "debug", skip(self))]
+    pub(crate) fn get_cascaded_config_for_path(
+        &self,
+        path: &Path,
+    ) -> Result<Option<Arc<CascadedConfig>>, Error> {
+        let Some(top_level_config_file_path) = self.config_file_path.as_ref() else {
+            return Ok(None);
+        };
+        let directory = match path.parent() {
+            Some(directory) if !directory.as_os_str().is_empty() => directory,
+            _ => Path::new("."),
+        };
+        let nearest_config_file_path = self
+            .cascaded_configs
+            .nearest_config_file_by_directory
+            .entry(directory.to_owned())
+            .or_insert_with(|| {
+                let nearest_config_file_path =
+                    find_config_file_from(directory.canonicalize().ok()?)?;
+                (top_level_config_file_path.canonicalize().ok().as_ref()
+                    != Some(&nearest_config_file_path))
+                .then_some(nearest_config_file_path)
+            })
+            .clone();
+        let Some(config_file_path) = nearest_config_file_path else {
+            return Ok(None);
+        };
+        if let Some(cascaded_config) = self
+            .cascaded_configs
+            .by_config_file_path
+            .get(&config_file_path)
+        {
+            return Ok(Some(cascaded_config.clone()));
+        }
+        let cascaded_config = Arc::new(self.load_cascaded_config(&config_file_path)?);
+        Ok(Some(
+            self.cascaded_configs
+                .by_config_file_path
+                .entry(config_file_path)
+                .or_insert(cascaded_config)
+                .clone(),
+        ))
+    }
+
+    fn load_cascaded_config(&self, config_file_path: &Path) -> Result<CascadedConfig, Error> {
+        let config_file_cascade = load_config_file_cascade(Some(config_file_path))?;
+        let modified_timestamps = config_file_cascade
+            .iter()
+            .map(|parsed_config_file| {
+                (
+                    parsed_config_file.path.clone(),
+                    get_modified_timestamp(&parsed_config_file.path),
+                )
+            })
+            .collect();
+        let (rule_configurations, rule_configuration_origins) =
+            resolve_rule_configurations(&config_file_cascade, &self.all_plugins)?;
+        self.validate_rule_names(&rule_configurations, &config_file_cascade)?;
+        self.validate_rule_options(&rule_configurations, &config_file_cascade)?;
+        let per_config_context = PerConfigContextTryBuilder {
+            instantiated_rules: self
+                .get_instantiated_rules_for_cascaded_config(&rule_configurations)?,
+            aggregated_queries_builder: |instantiated_rules| {
+                AggregatedQueries::new(instantiated_rules)
+            },
+        }
+        .try_build()?;
+        Ok(CascadedConfig {
+            config_file_path: config_file_path.to_owned(),
+            rule_configurations,
+            rule_configuration_origins,
+            per_config_context,
+            modified_timestamps,
+        })
+    }
+}
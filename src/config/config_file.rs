@@ -9,12 +9,90 @@ use serde::Deserialize;
 use tracing::instrument;
 
 use super::{ErrorLevel, RuleConfiguration};
-use crate::{configuration::ConfigurationReference, rule::RuleOptions};
+use crate::{configuration::ConfigurationReference, rule::RuleOptions, Error, SourceLocation};
 
 #[derive(Clone)]
 pub struct ParsedConfigFile {
     pub path: PathBuf,
     pub content: ParsedConfigFileContent,
+    pub raw: String,
+}
+
+impl ParsedConfigFile {
+    // Best-effort, since serde_yaml doesn't expose spans. Only understands
+    // block mappings, and gives up rather than guess (eg on flow mappings)
+    pub fn find_rule_location(&self, rule_name: &str) -> Option<SourceLocation> {
+        self.find_key_path(["rules", rule_name])
+    }
+
+    pub fn find_rule_options_location(
+        &self,
+        rule_name: &str,
+        options_path: &[String],
+    ) -> Option<SourceLocation> {
+        self.find_key_path(
+            ["rules", rule_name, "options"]
+                .into_iter()
+                .chain(options_path.iter().map(|key| &**key)),
+        )
+    }
+
+    fn find_key_path<'a>(&self, keys: impl IntoIterator<Item = &'a str>) -> Option<SourceLocation> {
+        let mut parent: Option<(usize, usize)> = None;
+        for key in keys {
+            parent = Some(self.find_key(key, parent)?);
+        }
+        let (line_index, indentation) = parent?;
+        Some(SourceLocation {
+            path: self.path.clone(),
+            line: line_index + 1,
+            column: indentation + 1,
+        })
+    }
+
+    // Looks for `key` among the direct children of the mapping under `parent`
+    // (given as line index and indentation), or at the top level. Only counts
+    // if it's found exactly once
+    fn find_key(&self, key: &str, parent: Option<(usize, usize)>) -> Option<(usize, usize)> {
+        let candidates = [
+            format!("{key}:"),
+            format!("\"{key}\":"),
+            format!("'{key}':"),
+        ];
+        let mut child_indentation: Option<usize> = parent.is_none().then_some(0);
+        let mut found: Option<(usize, usize)> = None;
+        for (line_index, line) in self
+            .raw
+            .lines()
+            .enumerate()
+            .skip(parent.map_or(0, |(line_index, _)| line_index + 1))
+        {
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let indentation = line.len() - trimmed.len();
+            if parent.is_some_and(|(_, parent_indentation)| indentation <= parent_indentation) {
+                break;
+            }
+            // anything more deeply indented belongs to a grandchild
+            if indentation != *child_indentation.get_or_insert(indentation) {
+                continue;
+            }
+            let is_match = candidates.iter().any(|candidate| {
+                trimmed
+                    .strip_prefix(&**candidate)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+            });
+            if is_match {
+                if found.is_some() {
+                    return None;
+                }
+                found = Some((line_index, indentation));
+            }
+        }
+        found
+    }
 }
 
 pub type Plugins = HashMap<String, PluginSpecValue>;
@@ -24,22 +102,35 @@ pub type Rules = HashMap<String, RuleConfigurationValue>;
 #[derive(Clone, Deserialize)]
 #[serde(rename_all = "kebab-case", deny_unknown_fields)]
 pub struct ParsedConfigFileContent {
+    #[serde(default)]
     pub plugins: Plugins,
     #[serde(default)]
     pub rules: Rules,
     pub tree_sitter_lint_dependency: Option<TreeSitterLintDependencySpec>,
     #[serde(default)]
     pub extends: Vec<ConfigurationReference>,
+    // stops config files in ancestor directories from getting merged in
+    #[serde(default)]
+    pub root: bool,
 }
 
 #[derive(Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct TreeSitterLintDependencySpec {
     pub path: PathBuf,
 }
 
-#[derive(Clone, Deserialize)]
+#[derive(Clone, Default, Deserialize, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
 pub struct PluginSpecValue {
     pub path: Option<PathBuf>,
+    pub version: Option<String>,
+    pub git: Option<String>,
+    pub rev: Option<String>,
+    pub tag: Option<String>,
+    pub branch: Option<String>,
+    // if the crate isn't named tree-sitter-lint-plugin-<plugin name>
+    pub package: Option<String>,
 }
 
 #[derive(Builder, Clone, Deserialize)]
@@ -60,27 +151,79 @@ impl RuleConfigurationValue {
     }
 }
 
-pub fn load_config_file() -> ParsedConfigFile {
-    let config_file_path = find_config_file();
+pub fn load_config_file(config_file_path: Option<&Path>) -> Result<ParsedConfigFile, Error> {
+    let config_file_path = match config_file_path {
+        Some(config_file_path) => config_file_path.to_owned(),
+        None => find_config_file()?,
+    };
     let config_file_contents =
-        fs::read_to_string(&config_file_path).expect("Couldn't read config file contents");
-    let parsed = serde_yaml::from_str(&config_file_contents).expect("Couldn't parse config file");
-
-    ParsedConfigFile {
+        fs::read_to_string(&config_file_path).map_err(|source| Error::ConfigFileRead {
+            path: config_file_path.clone(),
+            source,
+        })?;
+    let parsed =
+        serde_yaml::from_str(&config_file_contents).map_err(|source| Error::ConfigFileParse {
+            location: source.location().map(|location| SourceLocation {
+                path: config_file_path.clone(),
+                line: location.line(),
+                column: location.column(),
+            }),
+            path: config_file_path.clone(),
+            source,
+        })?;
+
+    Ok(ParsedConfigFile {
         path: config_file_path,
         content: parsed,
+        raw: config_file_contents,
+    })
+}
+
+// Outermost first, ie in the order they should get merged
+pub fn load_config_file_cascade(
+    config_file_path: Option<&Path>,
+) -> Result<Vec<ParsedConfigFile>, Error> {
+    let mut cascade = vec![load_config_file(config_file_path)?];
+    loop {
+        let outermost = cascade.last().unwrap();
+        if outermost.content.root {
+            break;
+        }
+        let absolute_path = canonicalize_config_file_path(&outermost.path)?;
+        let Some(parent_config_file_path) = absolute_path
+            .parent()
+            .and_then(Path::parent)
+            .and_then(find_config_file_from)
+        else {
+            break;
+        };
+        cascade.push(load_config_file(Some(&parent_config_file_path))?);
     }
+    cascade.reverse();
+    Ok(cascade)
+}
+
+pub(crate) fn canonicalize_config_file_path(config_file_path: &Path) -> Result<PathBuf, Error> {
+    config_file_path
+        .canonicalize()
+        .map_err(|source| Error::ConfigFileRead {
+            path: config_file_path.to_owned(),
+            source,
+        })
 }
 
-const CONFIG_FILENAME: &str = ".tree-sitter-lint.yml";
+pub(crate) const CONFIG_FILENAME: &str = ".tree-sitter-lint.yml";
 
 #[instrument]
-pub fn find_config_file() -> PathBuf {
-    find_filename_in_ancestor_directory(
-        CONFIG_FILENAME,
-        env::current_dir().expect("Couldn't get current directory"),
-    )
-    .expect("Couldn't find config file")
+pub fn find_config_file() -> Result<PathBuf, Error> {
+    let current_directory = env::current_dir().map_err(Error::CurrentDirectory)?;
+    find_config_file_from(&current_directory).ok_or(Error::ConfigFileNotFound {
+        starting_directory: current_directory,
+    })
+}
+
+pub fn find_config_file_from(starting_directory: impl Into<PathBuf>) -> Option<PathBuf> {
+    find_filename_in_ancestor_directory(CONFIG_FILENAME, starting_directory.into())
 }
 
 // https://codereview.stackexchange.com/a/236771
@@ -1,81 +1,211 @@
 use std::{
-    collections::HashMap,
-    path::PathBuf,
-    sync::{Arc, OnceLock},
+    collections::{BTreeSet, HashMap},
+    mem,
+    path::{Path, PathBuf},
+    slice,
+    sync::{Arc, Mutex, OnceLock},
 };
 
-use clap::Parser;
+use clap::{ArgGroup, Parser, Subcommand};
 use derive_builder::Builder;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
 use tracing::{instrument, trace_span};
 
 use crate::{
+    configuration::ConfigurationReference,
     environment::Environment,
+    error::LocationSuffix,
+    formatter::{get_builtin_formatter, Formatter, DEFAULT_FORMAT},
+    get_supported_language_language_for_path,
     rule::{InstantiatedRule, Rule, RuleOptions},
-    Plugin,
+    timing::{get_timing_limit_from_env, Timings},
+    Error, Plugin,
 };
 
+mod cascade;
 mod config_file;
+pub(crate) use cascade::CascadedConfigs;
+pub(crate) use config_file::{canonicalize_config_file_path, CONFIG_FILENAME};
 pub use config_file::{
-    find_config_file, load_config_file, ParsedConfigFile, Plugins, RuleConfigurationValue,
+    find_config_file, find_config_file_from, load_config_file, load_config_file_cascade,
+    ParsedConfigFile, PluginSpecValue, Plugins, RuleConfigurationValue,
     RuleConfigurationValueBuilder, Rules, TreeSitterLintDependencySpec,
 };
 
-use self::config_file::ParsedConfigFileContent;
-
-fn parse_configuration_reference(configuration_reference: &str) -> (&str, &str) {
+fn parse_configuration_reference(configuration_reference: &str) -> Result<(&str, &str), Error> {
     let mut chunks = configuration_reference.split('/');
-    let plugin_name = chunks.next().unwrap();
-    let configuration_name = chunks.next().unwrap();
-    assert!(chunks.next().is_none());
-    (plugin_name, configuration_name)
+    match (chunks.next(), chunks.next(), chunks.next()) {
+        (Some(plugin_name), Some(configuration_name), None)
+            if !plugin_name.is_empty() && !configuration_name.is_empty() =>
+        {
+            Ok((plugin_name, configuration_name))
+        }
+        _ => Err(Error::InvalidConfigurationReference {
+            reference: configuration_reference.to_owned(),
+        }),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RuleConfigurationOrigin {
+    ConfigFile,
+    // outermost first, so the last reference is the one that set it
+    Extends(Vec<ConfigurationReference>),
+}
+
+type RulesWithOrigins = HashMap<String, (RuleConfigurationValue, RuleConfigurationOrigin)>;
+
+fn is_config_file_reference(configuration_reference: &str) -> bool {
+    configuration_reference.ends_with(".yml") || configuration_reference.ends_with(".yaml")
 }
 
+#[allow(clippy::too_many_arguments)]
 fn add_rules_from_configuration_reference(
-    all_rules_by_name: &mut Rules,
+    all_rules_by_name: &mut RulesWithOrigins,
     configuration_reference: &str,
+    // for resolving relative config file references, None when extending from
+    // a plugin configuration
+    base_directory: Option<&Path>,
     all_plugins: &[Plugin],
-) {
-    let (plugin_name, configuration_name) = parse_configuration_reference(configuration_reference);
-    let plugin = all_plugins
-        .into_iter()
-        .find(|plugin| plugin.name == plugin_name)
-        .unwrap();
-    let configuration = plugin.configs.get(configuration_name).unwrap();
-    configuration.extends.iter().for_each(|extend| {
-        add_rules_from_configuration_reference(all_rules_by_name, extend, all_plugins);
-    });
-    all_rules_by_name.extend(
-        configuration
-            .rules
-            .iter()
-            .map(|(key, value)| (key.clone(), value.clone())),
-    );
+    extends_chain: &[ConfigurationReference],
+    // (canonicalized) config files currently being extended from
+    extended_config_files: &[PathBuf],
+) -> Result<(), Error> {
+    let extends_chain = extends_chain
+        .iter()
+        .cloned()
+        .chain([configuration_reference.to_owned()])
+        .collect::<Vec<_>>();
+    let (extends, rules, base_directory, extended_config_files) =
+        if is_config_file_reference(configuration_reference) {
+            let base_directory =
+                base_directory.ok_or_else(|| Error::InvalidConfigurationReference {
+                    reference: configuration_reference.to_owned(),
+                })?;
+            let parsed_config_file =
+                load_config_file(Some(&base_directory.join(configuration_reference)))?;
+            let canonical_path = canonicalize_config_file_path(&parsed_config_file.path)?;
+            if extended_config_files.contains(&canonical_path) {
+                return Err(Error::ExtendsCycle {
+                    chain: extends_chain,
+                });
+            }
+            let base_directory = canonical_path.parent().unwrap().to_owned();
+            let extended_config_files = extended_config_files
+                .iter()
+                .cloned()
+                .chain([canonical_path])
+                .collect::<Vec<_>>();
+            (
+                parsed_config_file.content.extends,
+                parsed_config_file.content.rules,
+                Some(base_directory),
+                extended_config_files,
+            )
+        } else {
+            if extends_chain[..extends_chain.len() - 1]
+                .iter()
+                .any(|extend| extend == configuration_reference)
+            {
+                return Err(Error::ExtendsCycle {
+                    chain: extends_chain,
+                });
+            }
+            let (plugin_name, configuration_name) =
+                parse_configuration_reference(configuration_reference)?;
+            let plugin = all_plugins
+                .into_iter()
+                .find(|plugin| plugin.name == plugin_name)
+                .ok_or_else(|| Error::UnknownPlugin {
+                    plugin_name: plugin_name.to_owned(),
+                    reference: configuration_reference.to_owned(),
+                })?;
+            let configuration = plugin.configs.get(configuration_name).ok_or_else(|| {
+                Error::UnknownConfiguration {
+                    plugin_name: plugin_name.to_owned(),
+                    configuration_name: configuration_name.to_owned(),
+                }
+            })?;
+            (
+                configuration.extends.clone(),
+                configuration.rules.clone(),
+                None,
+                extended_config_files.to_owned(),
+            )
+        };
+    for extend in &extends {
+        add_rules_from_configuration_reference(
+            all_rules_by_name,
+            extend,
+            base_directory.as_deref(),
+            all_plugins,
+            &extends_chain,
+            &extended_config_files,
+        )?;
+    }
+    all_rules_by_name.extend(rules.into_iter().map(|(key, value)| {
+        (
+            key,
+            (
+                value,
+                RuleConfigurationOrigin::Extends(extends_chain.clone()),
+            ),
+        )
+    }));
+    Ok(())
 }
 
 fn resolve_rule_configurations(
-    config_file_content: &ParsedConfigFileContent,
+    config_file_cascade: &[ParsedConfigFile],
     all_plugins: &[Plugin],
-) -> Vec<RuleConfiguration> {
-    let mut all_rules_by_name = Rules::default();
-    config_file_content.extends.iter().for_each(|extend| {
-        add_rules_from_configuration_reference(&mut all_rules_by_name, extend, all_plugins);
-    });
-    all_rules_by_name.extend(
-        config_file_content
-            .rules
-            .iter()
-            .map(|(key, value)| (key.clone(), value.clone())),
-    );
-    all_rules_by_name
+) -> Result<
+    (
+        Vec<RuleConfiguration>,
+        HashMap<String, RuleConfigurationOrigin>,
+    ),
+    Error,
+> {
+    let mut all_rules_by_name = RulesWithOrigins::default();
+    for parsed_config_file in config_file_cascade {
+        let config_file_content = &parsed_config_file.content;
+        let canonical_path = canonicalize_config_file_path(&parsed_config_file.path)?;
+        for extend in &config_file_content.extends {
+            add_rules_from_configuration_reference(
+                &mut all_rules_by_name,
+                extend,
+                canonical_path.parent(),
+                all_plugins,
+                &[],
+                slice::from_ref(&canonical_path),
+            )?;
+        }
+        all_rules_by_name.extend(config_file_content.rules.iter().map(|(key, value)| {
+            (
+                key.clone(),
+                (value.clone(), RuleConfigurationOrigin::ConfigFile),
+            )
+        }));
+    }
+    Ok(all_rules_by_name
         .into_iter()
-        .map(|(rule_name, rule_config)| rule_config.to_rule_configuration(rule_name))
-        .collect()
+        .map(|(rule_name, (rule_config, origin))| {
+            (
+                rule_config.to_rule_configuration(&rule_name),
+                (rule_name, origin),
+            )
+        })
+        .unzip())
 }
 
-#[derive(Builder, Debug, Default, Parser)]
+#[derive(Builder, Clone, Debug, Default, Parser)]
 #[builder(default, setter(into, strip_option))]
+#[command(group(ArgGroup::new("changed_files").args(["changed_since", "staged"])))]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     pub paths: Vec<PathBuf>,
 
     #[arg(long)]
@@ -84,11 +214,139 @@ pub struct Args {
     #[arg(long)]
     pub fix: bool,
 
+    /// Like --fix, but prompt before applying each fix
+    #[arg(long, conflicts_with_all = ["stdin", "watch"])]
+    pub fix_interactive: bool,
+
     #[arg(long)]
     pub report_fixed_violations: bool,
 
     #[arg(long)]
     pub force_rebuild: bool,
+
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+
+    #[arg(long)]
+    pub trace: Option<PathBuf>,
+
+    #[arg(long, num_args = 0..=1, default_missing_value = "10")]
+    pub timing: Option<usize>,
+
+    #[arg(long)]
+    pub timing_json: Option<PathBuf>,
+
+    /// Print the resolved configuration, optionally narrowed down to what
+    /// applies to the given file
+    #[arg(long, value_name = "FILE")]
+    pub print_config: Option<Option<PathBuf>>,
+
+    /// Only lint files changed relative to this git ref
+    #[arg(long, value_name = "REF")]
+    pub changed_since: Option<String>,
+
+    /// Only lint files with staged changes, using their staged contents
+    #[arg(long, conflicts_with_all = ["fix", "fix_interactive"])]
+    pub staged: bool,
+
+    /// Drop violations that don't touch any changed lines
+    #[arg(long, requires = "changed_files")]
+    pub changed_lines_only: bool,
+
+    /// Don't report violations recorded in this baseline file
+    #[arg(long, value_name = "FILE")]
+    pub baseline: Option<PathBuf>,
+
+    /// Record all current violations in the baseline file
+    #[arg(
+        long,
+        requires = "baseline",
+        conflicts_with_all = ["paths", "changed_files", "stdin"]
+    )]
+    pub write_baseline: bool,
+
+    /// Report baseline entries that no longer occur
+    #[arg(
+        long,
+        requires = "baseline",
+        conflicts_with_all = ["write_baseline", "paths", "changed_files", "stdin"]
+    )]
+    pub report_stale_baseline_entries: bool,
+
+    /// Lint source read from stdin instead of files. In --fix mode the fixed
+    /// source gets written to stdout
+    #[arg(
+        long,
+        requires = "stdin_filename",
+        conflicts_with_all = ["paths", "changed_files"]
+    )]
+    pub stdin: bool,
+
+    /// The path that the source read from stdin should be treated as having,
+    /// for detecting its language and resolving its config
+    #[arg(long, value_name = "PATH", requires = "stdin")]
+    pub stdin_filename: Option<PathBuf>,
+
+    /// Keep running and re-lint files as they change
+    #[arg(
+        long,
+        conflicts_with_all = [
+            "fix",
+            "stdin",
+            "changed_files",
+            "write_baseline",
+            "output_file"
+        ]
+    )]
+    pub watch: bool,
+
+    /// One of the built-in formatters (stylish [default], compact,
+    /// checkstyle, junit, github, gitlab) or a plugin-provided
+    /// `<plugin>/<formatter>`
+    #[arg(long)]
+    pub format: Option<String>,
+
+    /// Show the offending source code under each violation
+    #[arg(long)]
+    pub code_frame: bool,
+
+    /// Write the formatted violations to this file instead of stdout
+    #[arg(long, value_name = "FILE")]
+    pub output_file: Option<PathBuf>,
+
+    /// Only report violations from rules configured as `error` (warnings
+    /// still count towards --max-warnings)
+    #[arg(long)]
+    pub quiet: bool,
+
+    /// Exit with a failure status if there are more than this many warnings
+    #[arg(long, value_name = "N")]
+    pub max_warnings: Option<usize>,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum Command {
+    /// Print a JSON Schema for the config file
+    Schema,
+    /// List all rules provided by the configured plugins
+    ListRules,
+    /// Print a rule's full documentation and metadata
+    Explain {
+        /// The (plugin-prefixed) rule name
+        rule: String,
+    },
+    /// Keep rules instantiated and serve lint runs over a Unix socket in
+    /// .tree-sitter-lint/
+    Daemon,
+    /// Generate a Markdown page per rule plus an index
+    Docs {
+        /// Only document this plugin's rules
+        #[arg(long)]
+        plugin: Option<String>,
+
+        #[arg(long, default_value = "docs/rules")]
+        output_dir: PathBuf,
+    },
 }
 
 impl Args {
@@ -96,33 +354,76 @@ impl Args {
         self,
         all_plugins: Vec<Plugin>,
         all_standalone_rules: Vec<Arc<dyn Rule>>,
-    ) -> Config {
-        let ParsedConfigFile {
-            path: config_file_path,
-            content: config_file_content,
-        } = load_config_file();
+    ) -> Result<Config, Error> {
+        let config_file_cascade = load_config_file_cascade(self.config.as_deref())?;
         let Args {
+            command,
             rule,
             fix,
+            fix_interactive,
             report_fixed_violations,
             force_rebuild,
             paths,
+            timing,
+            timing_json,
+            print_config,
+            changed_since,
+            staged,
+            changed_lines_only,
+            baseline,
+            write_baseline,
+            report_stale_baseline_entries,
+            stdin,
+            stdin_filename,
+            format,
+            code_frame,
+            output_file,
+            quiet,
+            max_warnings,
+            ..
         } = self;
-        let rule_configurations = resolve_rule_configurations(&config_file_content, &all_plugins);
-        Config {
+        let (rule_configurations, rule_configuration_origins) =
+            resolve_rule_configurations(&config_file_cascade, &all_plugins)?;
+        let config = Config {
             rule,
             all_standalone_rules,
             all_plugins,
-            fix,
+            fix: fix || fix_interactive,
+            fix_interactive,
             report_fixed_violations,
             paths,
-            config_file_path: Some(config_file_path),
+            config_file_path: Some(config_file_cascade.last().unwrap().path.clone()),
             rule_configurations,
+            rule_configuration_origins,
             rules_by_plugin_prefixed_name: Default::default(),
             force_rebuild,
             single_fixing_pass: Default::default(),
             environment: Default::default(),
-        }
+            timing: timing.or_else(get_timing_limit_from_env),
+            timing_json_path: timing_json,
+            timings: Default::default(),
+            cascaded_configs: Default::default(),
+            rule_options_validated: true,
+            warnings: Default::default(),
+            command,
+            print_config,
+            changed_since,
+            staged,
+            changed_lines_only,
+            baseline_path: baseline,
+            write_baseline,
+            report_stale_baseline_entries,
+            stdin_filename: stdin_filename.filter(|_| stdin),
+            format: format.unwrap_or_else(|| DEFAULT_FORMAT.to_owned()),
+            code_frame,
+            output_file,
+            quiet,
+            max_warnings,
+        };
+        config.validate_rule_names(&config.rule_configurations, &config_file_cascade)?;
+        config.validate_rule_options(&config.rule_configurations, &config_file_cascade)?;
+        config.get_formatter()?;
+        Ok(config)
     }
 }
 
@@ -142,6 +443,10 @@ pub struct Config {
     #[builder(default)]
     pub fix: bool,
 
+    // implies `fix`
+    #[builder(default)]
+    pub fix_interactive: bool,
+
     #[builder(default)]
     pub report_fixed_violations: bool,
 
@@ -150,6 +455,9 @@ pub struct Config {
 
     pub rule_configurations: Vec<RuleConfiguration>,
 
+    #[builder(default)]
+    pub rule_configuration_origins: HashMap<String, RuleConfigurationOrigin>,
+
     #[allow(clippy::type_complexity)]
     #[builder(setter(skip))]
     rules_by_plugin_prefixed_name: OnceLock<HashMap<String, (Arc<dyn Rule>, Option<PluginIndex>)>>,
@@ -165,11 +473,77 @@ pub struct Config {
 
     #[builder(default)]
     pub paths: Vec<PathBuf>,
+
+    #[builder(default)]
+    pub timing: Option<usize>,
+
+    #[builder(default)]
+    pub timing_json_path: Option<PathBuf>,
+
+    #[builder(setter(skip))]
+    pub timings: Timings,
+
+    #[builder(setter(skip))]
+    cascaded_configs: Arc<CascadedConfigs>,
+
+    // Rule configurations loaded from config files get validated up front (so
+    // that errors can point into the config file), otherwise it happens when
+    // instantiating the rules
+    #[builder(setter(skip))]
+    rule_options_validated: bool,
+
+    // Eg about ignored rule options, for the CLI/daemon/LSP to each surface
+    // however they do
+    #[builder(setter(skip))]
+    warnings: Mutex<Vec<String>>,
+
+    #[builder(default)]
+    pub command: Option<Command>,
+
+    #[builder(default)]
+    pub print_config: Option<Option<PathBuf>>,
+
+    #[builder(default)]
+    pub changed_since: Option<String>,
+
+    #[builder(default)]
+    pub staged: bool,
+
+    #[builder(default)]
+    pub changed_lines_only: bool,
+
+    #[builder(default)]
+    pub baseline_path: Option<PathBuf>,
+
+    #[builder(default)]
+    pub write_baseline: bool,
+
+    #[builder(default)]
+    pub report_stale_baseline_entries: bool,
+
+    // set iff linting stdin
+    #[builder(default)]
+    pub stdin_filename: Option<PathBuf>,
+
+    #[builder(default = "DEFAULT_FORMAT.to_owned()")]
+    pub format: String,
+
+    #[builder(default)]
+    pub code_frame: bool,
+
+    #[builder(default)]
+    pub output_file: Option<PathBuf>,
+
+    #[builder(default)]
+    pub quiet: bool,
+
+    #[builder(default)]
+    pub max_warnings: Option<usize>,
 }
 
 impl Config {
     #[allow(clippy::type_complexity)]
-    fn get_rules_by_plugin_prefixed_name(
+    pub(crate) fn get_rules_by_plugin_prefixed_name(
         &self,
     ) -> &HashMap<String, (Arc<dyn Rule>, Option<PluginIndex>)> {
         self.rules_by_plugin_prefixed_name.get_or_init(|| {
@@ -201,21 +575,93 @@ impl Config {
         })
     }
 
+    fn validate_rule_names(
+        &self,
+        rule_configurations: &[RuleConfiguration],
+        config_file_cascade: &[ParsedConfigFile],
+    ) -> Result<(), Error> {
+        let rules_by_plugin_prefixed_name = self.get_rules_by_plugin_prefixed_name();
+        match rule_configurations
+            .iter()
+            .find(|rule_config| !rules_by_plugin_prefixed_name.contains_key(&rule_config.name))
+        {
+            Some(rule_config) => Err(Error::UnknownRule {
+                rule_name: rule_config.name.clone(),
+                // innermost config file that mentions it
+                location: config_file_cascade
+                    .iter()
+                    .rev()
+                    .find_map(|parsed_config_file| {
+                        parsed_config_file.find_rule_location(&rule_config.name)
+                    }),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn validate_rule_options<'a>(
+        &self,
+        rule_configurations: impl IntoIterator<Item = &'a RuleConfiguration>,
+        config_file_cascade: &[ParsedConfigFile],
+    ) -> Result<(), Error> {
+        let rules_by_plugin_prefixed_name = self.get_rules_by_plugin_prefixed_name();
+        for rule_config in rule_configurations {
+            let Some((rule, _)) = rules_by_plugin_prefixed_name.get(&rule_config.name) else {
+                continue;
+            };
+            if !rule.accepts_options() {
+                if rule_config.options.is_some() {
+                    let location =
+                        config_file_cascade
+                            .iter()
+                            .rev()
+                            .find_map(|parsed_config_file| {
+                                parsed_config_file
+                                    .find_rule_options_location(&rule_config.name, &[])
+                            });
+                    self.warnings.lock().unwrap().push(format!(
+                        "rule '{}' doesn't take options, ignoring them{}",
+                        rule_config.name,
+                        LocationSuffix(location.as_ref())
+                    ));
+                }
+                continue;
+            }
+            rule.validate_options(rule_config.options.as_ref())
+                .map_err(|source| Error::InvalidRuleOptions {
+                    rule_name: rule_config.name.clone(),
+                    location: config_file_cascade
+                        .iter()
+                        .rev()
+                        .find_map(|parsed_config_file| {
+                            parsed_config_file
+                                .find_rule_options_location(&rule_config.name, &source.path)
+                        }),
+                    source,
+                })?;
+        }
+        Ok(())
+    }
+
     #[allow(clippy::type_complexity)]
-    #[instrument(level = "trace", skip(self))]
-    fn get_active_rules_and_associated_plugins_and_options(
+    #[instrument(level = "trace", skip_all)]
+    fn get_active_rules_and_associated_plugins_and_options<'a>(
         &self,
-    ) -> Vec<(Arc<dyn Rule>, Option<PluginIndex>, &RuleConfiguration)> {
+        rule_configurations: &'a [RuleConfiguration],
+    ) -> Result<Vec<(Arc<dyn Rule>, Option<PluginIndex>, &'a RuleConfiguration)>, Error> {
         let rules_by_plugin_prefixed_name = self.get_rules_by_plugin_prefixed_name();
-        self.rule_configurations
+        rule_configurations
             .iter()
             .filter(|rule_config| rule_config.level != ErrorLevel::Off)
             .map(|rule_config| {
                 let (rule, plugin_index) = rules_by_plugin_prefixed_name
                     .get(&rule_config.name)
-                    .unwrap_or_else(|| panic!("Unknown rule: '{}'", rule_config.name))
+                    .ok_or_else(|| Error::UnknownRule {
+                        rule_name: rule_config.name.clone(),
+                        location: None,
+                    })?
                     .clone();
-                (rule, plugin_index, rule_config)
+                Ok((rule, plugin_index, rule_config))
             })
             .collect()
     }
@@ -228,7 +674,7 @@ impl Config {
             Option<PluginIndex>,
             &'a RuleConfiguration,
         )>,
-    ) -> Vec<(Arc<dyn Rule>, Option<PluginIndex>, &'a RuleConfiguration)> {
+    ) -> Result<Vec<(Arc<dyn Rule>, Option<PluginIndex>, &'a RuleConfiguration)>, Error> {
         match self.rule.as_ref() {
             Some(rule_arg) => {
                 let filtered = active_rules_and_associated_plugins_and_options
@@ -236,36 +682,75 @@ impl Config {
                     .filter(|(rule, _, _)| &rule.meta().name == rule_arg)
                     .collect::<Vec<_>>();
                 if !filtered.is_empty() {
-                    return filtered;
+                    return Ok(filtered);
                 }
-                self.rule_argument_error();
+                Err(self.rule_argument_error(rule_arg))
             }
-            None => active_rules_and_associated_plugins_and_options,
+            None => Ok(active_rules_and_associated_plugins_and_options),
         }
     }
 
-    fn rule_argument_error(&self) -> ! {
-        let rule_arg = self.rule.as_ref().unwrap();
+    fn rule_argument_error(&self, rule_arg: &str) -> Error {
         if self
             .get_rules_by_plugin_prefixed_name()
             .contains_key(rule_arg)
         {
-            panic!("The '{rule_arg}' rule is configured as inactive");
+            Error::InactiveRule {
+                rule_name: rule_arg.to_owned(),
+            }
         } else {
-            panic!("Unknown rule: '{rule_arg}'");
+            Error::UnknownRule {
+                rule_name: rule_arg.to_owned(),
+                location: None,
+            }
         }
     }
 
     #[instrument(level = "debug", skip(self))]
-    pub fn get_instantiated_rules(&self) -> Vec<InstantiatedRule> {
+    pub fn get_instantiated_rules(&self) -> Result<Vec<InstantiatedRule>, Error> {
         let active_rules_and_associated_plugins_and_options =
-            self.get_active_rules_and_associated_plugins_and_options();
+            self.get_active_rules_and_associated_plugins_and_options(&self.rule_configurations)?;
         if active_rules_and_associated_plugins_and_options.is_empty() {
-            panic!("No configured active rules");
+            return Err(Error::NoActiveRules);
         }
         let active_rules_and_associated_plugins_and_options =
-            self.filter_based_on_rule_argument(active_rules_and_associated_plugins_and_options);
+            self.filter_based_on_rule_argument(active_rules_and_associated_plugins_and_options)?;
+        if !self.rule_options_validated {
+            self.validate_rule_options(
+                active_rules_and_associated_plugins_and_options
+                    .iter()
+                    .map(|(_, _, rule_config)| *rule_config),
+                &[],
+            )?;
+        }
+        Ok(self.instantiate_rules(active_rules_and_associated_plugins_and_options))
+    }
 
+    // Unlike get_instantiated_rules(), it's fine for a nested config file to
+    // end up with no active rules
+    fn get_instantiated_rules_for_cascaded_config(
+        &self,
+        rule_configurations: &[RuleConfiguration],
+    ) -> Result<Vec<InstantiatedRule>, Error> {
+        let mut active_rules_and_associated_plugins_and_options =
+            self.get_active_rules_and_associated_plugins_and_options(rule_configurations)?;
+        if let Some(rule_arg) = self.rule.as_ref() {
+            active_rules_and_associated_plugins_and_options
+                .retain(|(rule, _, _)| &rule.meta().name == rule_arg);
+        }
+        Ok(self.instantiate_rules(active_rules_and_associated_plugins_and_options))
+    }
+
+    // Expects the rule options to have already been validated
+    #[allow(clippy::type_complexity)]
+    fn instantiate_rules(
+        &self,
+        active_rules_and_associated_plugins_and_options: Vec<(
+            Arc<dyn Rule>,
+            Option<PluginIndex>,
+            &RuleConfiguration,
+        )>,
+    ) -> Vec<InstantiatedRule> {
         trace_span!("instantiate rules").in_scope(|| {
             active_rules_and_associated_plugins_and_options
                 .into_iter()
@@ -276,9 +761,155 @@ impl Config {
         })
     }
 
+    pub fn get_effective_config(&self, path: Option<&Path>) -> serde_json::Value {
+        self.get_effective_config_from(
+            path,
+            self.config_file_path.as_deref(),
+            &self.rule_configurations,
+            &self.rule_configuration_origins,
+        )
+    }
+
+    // Takes into account nested config files that apply to this file
+    pub fn get_effective_config_for_file(&self, path: &Path) -> Result<serde_json::Value, Error> {
+        Ok(match self.get_cascaded_config_for_path(path)? {
+            Some(cascaded_config) => self.get_effective_config_from(
+                Some(path),
+                Some(&cascaded_config.config_file_path),
+                &cascaded_config.rule_configurations,
+                &cascaded_config.rule_configuration_origins,
+            ),
+            None => self.get_effective_config(Some(path)),
+        })
+    }
+
+    fn get_effective_config_from(
+        &self,
+        path: Option<&Path>,
+        config_file_path: Option<&Path>,
+        rule_configurations: &[RuleConfiguration],
+        rule_configuration_origins: &HashMap<String, RuleConfigurationOrigin>,
+    ) -> serde_json::Value {
+        // `Some(None)` if it's not in a supported language
+        let supported_language_language = path.map(get_supported_language_language_for_path);
+        let rules_by_plugin_prefixed_name = self.get_rules_by_plugin_prefixed_name();
+        let mut languages: BTreeSet<String> = Default::default();
+        let rules = rule_configurations
+            .iter()
+            .filter_map(|rule_config| {
+                let rule_languages = rules_by_plugin_prefixed_name
+                    .get(&rule_config.name)
+                    .map(|(rule, _)| rule.meta().languages.clone())
+                    .unwrap_or_default();
+                if let Some(supported_language_language) = supported_language_language {
+                    if !supported_language_language.is_some_and(|supported_language_language| {
+                        rule_languages.contains(&supported_language_language.supported_language())
+                    }) {
+                        return None;
+                    }
+                }
+                if rule_config.level != ErrorLevel::Off {
+                    languages.extend(
+                        rule_languages
+                            .iter()
+                            .flat_map(|language| language.all_supported_language_languages())
+                            .filter(|&&language| {
+                                supported_language_language.map_or(
+                                    true,
+                                    |supported_language_language| {
+                                        supported_language_language == Some(language)
+                                    },
+                                )
+                            })
+                            .map(|language| format!("{language:?}")),
+                    );
+                }
+                Some((
+                    rule_config.name.clone(),
+                    json!({
+                        "level": rule_config.level,
+                        "options": rule_config.options,
+                        "origin": rule_configuration_origins.get(&rule_config.name),
+                    }),
+                ))
+            })
+            .collect::<serde_json::Map<_, _>>();
+        let linted = !languages.is_empty();
+        let mut effective_config = json!({
+            "configFile": config_file_path,
+            "file": path,
+            "languages": languages,
+            "environment": self.environment,
+            "rules": rules,
+        });
+        // eg its extension isn't recognized, or none of the active rules are
+        // for its language
+        if path.is_some() {
+            effective_config["linted"] = linted.into();
+        }
+        effective_config
+    }
+
+    pub(crate) fn all_plugins(&self) -> &[Plugin] {
+        &self.all_plugins
+    }
+
     pub fn get_plugin_name(&self, plugin_index: PluginIndex) -> &str {
         &self.all_plugins[plugin_index].name
     }
+
+    fn get_rules(&self, plugin_index: Option<PluginIndex>) -> &[Arc<dyn Rule>] {
+        match plugin_index {
+            Some(plugin_index) => &self.all_plugins[plugin_index].rules,
+            None => &self.all_standalone_rules,
+        }
+    }
+
+    pub(crate) fn get_rule_index(
+        &self,
+        rule: &Arc<dyn Rule>,
+        plugin_index: Option<PluginIndex>,
+    ) -> usize {
+        self.get_rules(plugin_index)
+            .iter()
+            .position(|candidate| {
+                Arc::as_ptr(candidate) as *const () == Arc::as_ptr(rule) as *const ()
+            })
+            .expect("Rule should belong to its plugin")
+    }
+
+    pub(crate) fn get_plugin_prefixed_rule_name(
+        &self,
+        plugin_index: Option<PluginIndex>,
+        rule_index: usize,
+    ) -> String {
+        let rule_name = &self.get_rules(plugin_index)[rule_index].meta().name;
+        match plugin_index {
+            None => rule_name.clone(),
+            Some(plugin_index) => format!("{}/{rule_name}", self.get_plugin_name(plugin_index)),
+        }
+    }
+
+    // Each warning only gets returned once
+    pub fn take_warnings(&self) -> Vec<String> {
+        mem::take(&mut *self.warnings.lock().unwrap())
+    }
+
+    pub fn get_formatter(&self) -> Result<Arc<dyn Formatter>, Error> {
+        let unknown_formatter = || Error::UnknownFormatter {
+            name: self.format.clone(),
+        };
+        match self.format.split_once('/') {
+            Some((plugin_name, formatter_name)) => self
+                .all_plugins
+                .iter()
+                .find(|plugin| plugin.name == plugin_name)
+                .and_then(|plugin| plugin.formatters.get(formatter_name))
+                .cloned()
+                .ok_or_else(unknown_formatter),
+            None => get_builtin_formatter(&self.format).ok_or_else(unknown_formatter),
+        }
+    }
 }
 
 impl ConfigBuilder {
@@ -312,9 +943,10 @@ impl RuleConfiguration {
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub enum ErrorLevel {
     Error,
+    Warn,
     Off,
 }
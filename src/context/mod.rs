@@ -164,6 +164,7 @@ impl<'a, 'b> QueryMatchContext<'a, 'b> {
         debug!("reporting violation");
 
         let mut had_fixes = false;
+        let mut fixes: Option<Vec<PendingFix>> = Default::default();
         if self.file_run_context.config.fix {
             if let Some(fix) = violation.fix.as_ref() {
                 if !self.rule.meta.fixable {
@@ -173,14 +174,29 @@ impl<'a, 'b> QueryMatchContext<'a, 'b> {
                 fix(&mut fixer);
                 if let Some(pending_fixes) = fixer.into_pending_fixes() {
                     had_fixes = true;
+                    // so that the interactive review can show which violation
+                    // each fix is for
+                    if self.file_run_context.config.fix_interactive {
+                        fixes = Some(pending_fixes.clone());
+                    }
                     self.pending_fixes
                         .borrow_mut()
                         .get_or_insert_with(Default::default)
                         .extend(pending_fixes);
                 }
             }
+        } else if matches!(self.file_run_context.run_kind, RunKind::NonfixingForSlice)
+            && self.rule.meta.fixable
+        {
+            // for eg the LSP server to be able to offer the fix as a code
+            // action without having to re-run the linter
+            if let Some(fix) = violation.fix.as_ref() {
+                let mut fixer = Fixer::default();
+                fix(&mut fixer);
+                fixes = fixer.into_pending_fixes();
+            }
         }
-        let violation = violation.contextualize(self, had_fixes);
+        let violation = violation.contextualize(self, had_fixes, fixes);
         self.violations
             .borrow_mut()
             .get_or_insert_with(Default::default)
@@ -0,0 +1,270 @@
+use std::{
+    env, fs,
+    io::{BufRead, BufReader, Write},
+    iter,
+    os::unix::net::{UnixListener, UnixStream},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use clap::Parser;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use tracing::{debug, instrument};
+
+use crate::{
+    cli::PER_PROJECT_DIRECTORY_NAME,
+    config::{canonicalize_config_file_path, find_config_file, CascadedConfigs},
+    format_error, format_run_status, get_per_config_context, print_error,
+    run_with_per_config_context, Args, Config, Error, ExitStatus, FormattedRunStatus,
+    FromFileRunContextInstanceProviderFactory, PerConfigContext,
+};
+
+const DAEMON_SOCKET_FILENAME: &str = "daemon.sock";
+
+pub(crate) fn get_daemon_socket_path(config_file_path: &Path) -> PathBuf {
+    config_file_path
+        .parent()
+        .unwrap()
+        .join(PER_PROJECT_DIRECTORY_NAME)
+        .join(DAEMON_SOCKET_FILENAME)
+}
+
+// Subcommands and anything that outputs more than violations get run the
+// normal way
+pub(crate) fn can_forward_to_daemon(args: &Args) -> bool {
+    args.command.is_none()
+        && args.print_config.is_none()
+        && args.timing.is_none()
+        && args.timing_json.is_none()
+        && args.trace.is_none()
+        && !args.write_baseline
+        && !args.report_stale_baseline_entries
+        && !args.stdin
+        && !args.watch
+        && !args.fix_interactive
+        && args.output_file.is_none()
+}
+
+#[derive(Deserialize, Serialize)]
+pub(crate) struct DaemonRequest {
+    pub cwd: PathBuf,
+    pub args: Vec<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(rename_all = "camelCase", tag = "kind")]
+pub(crate) enum DaemonResponse {
+    Ran {
+        stdout: String,
+        stderr: String,
+        exit_code: i32,
+    },
+    // the local binary got rebuilt since the daemon started
+    Stale,
+}
+
+// Returns None if there's no (usable) daemon listening, in which case the
+// caller should fall back to a normal run
+#[instrument]
+pub(crate) fn forward_to_daemon(socket_path: &Path, args: Vec<String>) -> Option<i32> {
+    let stream = UnixStream::connect(socket_path).ok()?;
+    let request = DaemonRequest {
+        cwd: env::current_dir().ok()?,
+        args,
+    };
+    writeln!(&stream, "{}", serde_json::to_string(&request).unwrap()).ok()?;
+    let mut response = String::new();
+    BufReader::new(&stream).read_line(&mut response).ok()?;
+    match serde_json::from_str(&response).ok()? {
+        DaemonResponse::Ran {
+            stdout,
+            stderr,
+            exit_code,
+        } => {
+            print!("{stdout}");
+            eprint!("{stderr}");
+            Some(exit_code)
+        }
+        DaemonResponse::Stale => {
+            debug!("daemon is stale");
+            None
+        }
+    }
+}
+
+struct WarmPerConfigContext {
+    // everything that went into instantiating the rules
+    key: String,
+    per_config_context: PerConfigContext,
+    // nested config files' rules, which get instantiated as they're needed
+    cascaded_configs: Arc<CascadedConfigs>,
+}
+
+pub fn run_daemon(
+    args: Args,
+    args_to_config: impl Fn(Args) -> Result<Config, Error>,
+    from_file_run_context_instance_provider_factory: &dyn FromFileRunContextInstanceProviderFactory,
+) -> ExitStatus {
+    match serve(
+        args,
+        args_to_config,
+        from_file_run_context_instance_provider_factory,
+    ) {
+        Ok(()) => ExitStatus::Ok,
+        Err(error) => {
+            print_error(&error);
+            ExitStatus::SetupError
+        }
+    }
+}
+
+fn serve(
+    args: Args,
+    args_to_config: impl Fn(Args) -> Result<Config, Error>,
+    from_file_run_context_instance_provider_factory: &dyn FromFileRunContextInstanceProviderFactory,
+) -> Result<(), Error> {
+    // requests change the current directory, so this has to be absolute
+    let config_file_path = match args.config.as_ref() {
+        Some(config_file_path) => canonicalize_config_file_path(config_file_path)?,
+        None => find_config_file()?,
+    };
+    let socket_path = get_daemon_socket_path(&config_file_path);
+    if UnixStream::connect(&socket_path).is_ok() {
+        return Err(Error::DaemonAlreadyRunning { socket_path });
+    }
+    let _ = fs::remove_file(&socket_path);
+    let listener = fs::create_dir_all(socket_path.parent().unwrap())
+        .and_then(|_| UnixListener::bind(&socket_path))
+        .map_err(|source| Error::DaemonSocket {
+            path: socket_path.clone(),
+            source,
+        })?;
+    let current_exe = env::current_exe().ok();
+    let get_current_exe_modified_timestamp =
+        || -> Option<SystemTime> { current_exe.as_ref()?.metadata().ok()?.modified().ok() };
+    let initial_current_exe_modified_timestamp = get_current_exe_modified_timestamp();
+    eprintln!("Listening on {}", socket_path.display());
+
+    let mut warm_per_config_context: Option<WarmPerConfigContext> = Default::default();
+    for stream in listener.incoming() {
+        let Ok(stream) = stream else {
+            continue;
+        };
+        if get_current_exe_modified_timestamp() != initial_current_exe_modified_timestamp {
+            let _ = respond(&stream, &DaemonResponse::Stale);
+            break;
+        }
+        let mut request = String::new();
+        if BufReader::new(&stream).read_line(&mut request).is_err() {
+            continue;
+        }
+        let Ok(request) = serde_json::from_str::<DaemonRequest>(&request) else {
+            continue;
+        };
+        let response = handle_request(
+            request,
+            &config_file_path,
+            &args_to_config,
+            from_file_run_context_instance_provider_factory,
+            &mut warm_per_config_context,
+        );
+        let _ = respond(&stream, &response);
+    }
+    let _ = fs::remove_file(&socket_path);
+    Ok(())
+}
+
+fn respond(mut stream: &UnixStream, response: &DaemonResponse) -> std::io::Result<()> {
+    writeln!(stream, "{}", serde_json::to_string(response).unwrap())
+}
+
+#[instrument(skip_all, fields(cwd = ?request.cwd, args = ?request.args))]
+fn handle_request(
+    request: DaemonRequest,
+    config_file_path: &Path,
+    args_to_config: impl Fn(Args) -> Result<Config, Error>,
+    from_file_run_context_instance_provider_factory: &dyn FromFileRunContextInstanceProviderFactory,
+    warm_per_config_context: &mut Option<WarmPerConfigContext>,
+) -> DaemonResponse {
+    let setup_error = |message: String| DaemonResponse::Ran {
+        stdout: Default::default(),
+        stderr: format!("{message}\n"),
+        exit_code: ExitStatus::SetupError.code(),
+    };
+    if let Err(error) = env::set_current_dir(&request.cwd) {
+        return setup_error(format!(
+            "Couldn't change to directory {}: {error}",
+            request.cwd.display()
+        ));
+    }
+    let mut args =
+        match Args::try_parse_from(iter::once("tree-sitter-lint".to_owned()).chain(request.args)) {
+            Ok(args) => args,
+            Err(error) => return setup_error(error.to_string()),
+        };
+    args.config
+        .get_or_insert_with(|| config_file_path.to_owned());
+    let mut config = match args_to_config(args) {
+        Ok(config) => config,
+        Err(error) => return setup_error(format_error(&error)),
+    };
+    let key = get_per_config_context_key(&config);
+    match warm_per_config_context {
+        Some(warm_per_config_context) if warm_per_config_context.key == key => {
+            warm_per_config_context.cascaded_configs.clear_stale();
+            config.set_cascaded_configs(warm_per_config_context.cascaded_configs.clone());
+        }
+        _ => {
+            debug!("instantiating rules");
+            match get_per_config_context(&config) {
+                Ok(per_config_context) => {
+                    *warm_per_config_context = Some(WarmPerConfigContext {
+                        key,
+                        per_config_context,
+                        cascaded_configs: config.cascaded_configs().clone(),
+                    });
+                }
+                Err(error) => return setup_error(format_error(&error)),
+            }
+        }
+    }
+    let run_status = match run_with_per_config_context(
+        &config,
+        from_file_run_context_instance_provider_factory,
+        &warm_per_config_context.as_ref().unwrap().per_config_context,
+    ) {
+        Ok(run_status) => run_status,
+        Err(error) => {
+            return DaemonResponse::Ran {
+                stdout: Default::default(),
+                stderr: format!("{}\n", format_error(&error)),
+                exit_code: ExitStatus::for_error(&error).code(),
+            }
+        }
+    };
+    let FormattedRunStatus {
+        stdout,
+        stderr,
+        exit_status,
+    } = format_run_status(&config, run_status, |path| fs::read(path).ok());
+    DaemonResponse::Ran {
+        stdout,
+        stderr,
+        exit_code: exit_status.code(),
+    }
+}
+
+fn get_per_config_context_key(config: &Config) -> String {
+    serde_json::to_string(&(
+        &config.rule,
+        config
+            .rule_configurations
+            .iter()
+            .map(|rule_config| (&rule_config.name, rule_config.level, &rule_config.options))
+            .sorted_by(|(a, _, _), (b, _, _)| a.cmp(b))
+            .collect::<Vec<_>>(),
+    ))
+    .unwrap()
+}
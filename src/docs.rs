@@ -0,0 +1,94 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use itertools::Itertools;
+
+use crate::{explain::get_rule_docs_markdown, Config, Error};
+
+const INDEX_FILENAME: &str = "README.md";
+
+pub fn generate_rule_docs(
+    config: &Config,
+    plugin_name: Option<&str>,
+    output_directory: &Path,
+) -> Result<Vec<PathBuf>, Error> {
+    if let Some(plugin_name) = plugin_name {
+        if !config
+            .all_plugins()
+            .iter()
+            .any(|plugin| plugin.name == plugin_name)
+        {
+            return Err(Error::PluginNotFound {
+                plugin_name: plugin_name.to_owned(),
+            });
+        }
+    }
+
+    let mut written = vec![];
+    let mut index = match plugin_name {
+        Some(plugin_name) => format!("# {plugin_name} rules\n\n"),
+        None => "# Rules\n\n".to_owned(),
+    };
+    index.push_str("| Rule | Description | Languages | Fixable |\n");
+    index.push_str("| ---- | ----------- | --------- | ------- |\n");
+    for (plugin_prefixed_name, (rule, plugin_index)) in config
+        .get_rules_by_plugin_prefixed_name()
+        .iter()
+        .sorted_by(|(a, _), (b, _)| a.cmp(b))
+    {
+        let rule_plugin_name =
+            plugin_index.map(|plugin_index| config.get_plugin_name(plugin_index));
+        if plugin_name.is_some() && rule_plugin_name != plugin_name {
+            continue;
+        }
+        let meta = rule.meta();
+        // when documenting a single plugin its rules are referred to unprefixed
+        let rule_name = if plugin_name.is_some() {
+            &meta.name
+        } else {
+            plugin_prefixed_name
+        };
+        let relative_path = format!("{rule_name}.md");
+        let path = output_directory.join(&relative_path);
+        write_docs_file(
+            &path,
+            format!(
+                "{}\n",
+                get_rule_docs_markdown(rule_name, &meta, rule_plugin_name)
+            ),
+        )?;
+        written.push(path);
+
+        index.push_str(&format!(
+            "| [{rule_name}]({relative_path}) | {} | {} | {} |\n",
+            meta.docs
+                .as_ref()
+                .and_then(|docs| docs.description.as_ref())
+                .and_then(|description| description.trim().lines().next())
+                .unwrap_or_default()
+                .replace('|', "\\|"),
+            meta.languages
+                .iter()
+                .map(|language| format!("{language:?}"))
+                .join(", "),
+            if meta.fixable { "yes" } else { "" },
+        ));
+    }
+
+    let index_path = output_directory.join(INDEX_FILENAME);
+    write_docs_file(&index_path, index)?;
+    written.push(index_path);
+
+    Ok(written)
+}
+
+fn write_docs_file(path: &Path, contents: String) -> Result<(), Error> {
+    fs::create_dir_all(path.parent().unwrap())
+        .and_then(|_| fs::write(path, contents))
+        .map_err(|source| Error::DocsWrite {
+            path: path.to_owned(),
+            source,
+        })
+}
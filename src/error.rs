@@ -0,0 +1,241 @@
+use std::{fmt, io, path::PathBuf};
+
+use thiserror::Error;
+use tree_sitter_grep::tree_sitter::QueryError;
+
+use crate::{config::CONFIG_FILENAME, configuration::ConfigurationReference, RuleOptionsError};
+
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Couldn't get current directory: {0}")]
+    CurrentDirectory(#[source] io::Error),
+
+    #[error("Couldn't read from stdin: {0}")]
+    Stdin(#[source] io::Error),
+
+    #[error(
+        "Couldn't find a {CONFIG_FILENAME} config file in {} or any of its parent directories",
+        .starting_directory.display()
+    )]
+    ConfigFileNotFound { starting_directory: PathBuf },
+
+    #[error("Couldn't read config file {}: {source}", .path.display())]
+    ConfigFileRead {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Couldn't parse config file {}: {source}", .path.display())]
+    ConfigFileParse {
+        path: PathBuf,
+        location: Option<SourceLocation>,
+        #[source]
+        source: serde_yaml::Error,
+    },
+
+    #[error(
+        "Invalid configuration reference '{reference}', expected '<plugin>/<configuration>' or a \
+         path to a YAML config file"
+    )]
+    InvalidConfigurationReference { reference: String },
+
+    #[error("Cycle in extends: {}", .chain.join(" -> "))]
+    ExtendsCycle { chain: Vec<ConfigurationReference> },
+
+    #[error("Unknown plugin '{plugin_name}' in configuration reference '{reference}'")]
+    UnknownPlugin {
+        plugin_name: String,
+        reference: String,
+    },
+
+    #[error("Unknown plugin '{plugin_name}'")]
+    PluginNotFound { plugin_name: String },
+
+    #[error("Invalid spec for plugin '{plugin_name}': {reason}")]
+    InvalidPluginSpec {
+        plugin_name: String,
+        reason: &'static str,
+    },
+
+    #[error("Plugin '{plugin_name}' has no configuration named '{configuration_name}'")]
+    UnknownConfiguration {
+        plugin_name: String,
+        configuration_name: String,
+    },
+
+    #[error("Unknown rule: '{rule_name}'{}", LocationSuffix(.location.as_ref()))]
+    UnknownRule {
+        rule_name: String,
+        location: Option<SourceLocation>,
+    },
+
+    #[error(
+        "Invalid options for rule '{rule_name}': {source}{}",
+        LocationSuffix(.location.as_ref())
+    )]
+    InvalidRuleOptions {
+        rule_name: String,
+        location: Option<SourceLocation>,
+        #[source]
+        source: RuleOptionsError,
+    },
+
+    #[error("The '{rule_name}' rule is configured as inactive")]
+    InactiveRule { rule_name: String },
+
+    #[error("No configured active rules")]
+    NoActiveRules,
+
+    #[error("Unknown formatter '{name}'")]
+    UnknownFormatter { name: String },
+
+    #[error("Couldn't write output file {}: {source}", .path.display())]
+    OutputFileWrite {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Couldn't create trace file {}: {source}", .path.display())]
+    TraceFileCreate {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Couldn't write fixed files:{}", FixWriteFailures(.failures))]
+    FixWrite { failures: Vec<FixWriteFailure> },
+
+    #[error("Couldn't write docs file {}: {source}", .path.display())]
+    DocsWrite {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Couldn't read baseline file {}: {source}", .path.display())]
+    BaselineFileRead {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Couldn't parse baseline file {}: {source}", .path.display())]
+    BaselineFileParse {
+        path: PathBuf,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("Couldn't write baseline file {}: {source}", .path.display())]
+    BaselineFileWrite {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("`git {command}` failed: {message}")]
+    Git { command: String, message: String },
+
+    #[error("A daemon is already listening on {}", .socket_path.display())]
+    DaemonAlreadyRunning { socket_path: PathBuf },
+
+    #[error("The daemon is only supported on Unix")]
+    DaemonUnsupported,
+
+    #[error("The daemon has to be started with run_daemon(), not from a resolved config")]
+    DaemonFromConfig,
+
+    #[error("Couldn't listen on daemon socket {}: {source}", .path.display())]
+    DaemonSocket {
+        path: PathBuf,
+        #[source]
+        source: io::Error,
+    },
+
+    #[error("Couldn't watch for file changes: {0}")]
+    Watch(#[source] notify::Error),
+
+    #[error("Couldn't search for files: {0}")]
+    FileSearch(String),
+
+    #[error("Fixing isn't supported with a per-file callback, use run() instead")]
+    FixingWithPerFileCallback,
+
+    #[error("Invalid listener selector {selector:?} in rule '{rule_name}': {reason}")]
+    InvalidListenerSelector {
+        rule_name: String,
+        selector: String,
+        reason: &'static str,
+    },
+
+    #[error("Couldn't parse listener query for rule '{rule_name}': {source}\n\n{query_text}")]
+    Query {
+        rule_name: String,
+        query_text: String,
+        #[source]
+        source: QueryError,
+    },
+}
+
+impl Error {
+    pub fn location(&self) -> Option<&SourceLocation> {
+        match self {
+            Self::ConfigFileParse { location, .. }
+            | Self::UnknownRule { location, .. }
+            | Self::InvalidRuleOptions { location, .. } => location.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn query_offset(&self) -> Option<usize> {
+        match self {
+            Self::Query { source, .. } => Some(source.offset),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceLocation {
+    pub path: PathBuf,
+    // 1-based
+    pub line: usize,
+    // 1-based
+    pub column: usize,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}:{}", self.path.display(), self.line, self.column)
+    }
+}
+
+#[derive(Debug)]
+pub struct FixWriteFailure {
+    pub path: PathBuf,
+    pub source: io::Error,
+}
+
+struct FixWriteFailures<'a>(&'a [FixWriteFailure]);
+
+impl fmt::Display for FixWriteFailures<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for failure in self.0 {
+            write!(f, "\n  {}: {}", failure.path.display(), failure.source)?;
+        }
+        Ok(())
+    }
+}
+
+pub(crate) struct LocationSuffix<'a>(pub(crate) Option<&'a SourceLocation>);
+
+impl fmt::Display for LocationSuffix<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(location) => write!(f, " (at {location})"),
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use itertools::Itertools;
+
+use crate::{Config, Error, ErrorLevel, RuleDocs, RuleMeta};
+
+pub(crate) struct RuleListRow {
+    pub plugin: Option<String>,
+    pub name: String,
+    pub languages: String,
+    pub fixable: bool,
+    pub level: Option<ErrorLevel>,
+    pub description: Option<String>,
+}
+
+pub(crate) fn get_rule_list_rows(config: &Config) -> Vec<RuleListRow> {
+    config
+        .get_rules_by_plugin_prefixed_name()
+        .iter()
+        .sorted_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(plugin_prefixed_name, (rule, plugin_index))| {
+            let meta = rule.meta();
+            RuleListRow {
+                plugin: plugin_index
+                    .map(|plugin_index| config.get_plugin_name(plugin_index).to_owned()),
+                name: meta.name.clone(),
+                languages: meta
+                    .languages
+                    .iter()
+                    .map(|language| format!("{language:?}"))
+                    .join(", "),
+                fixable: meta.fixable,
+                level: get_configured_level(config, plugin_prefixed_name),
+                description: meta
+                    .docs
+                    .as_ref()
+                    .and_then(|docs| docs.description.as_ref())
+                    .and_then(|description| description.trim().lines().next())
+                    .map(ToOwned::to_owned),
+            }
+        })
+        .collect()
+}
+
+fn get_configured_level(config: &Config, plugin_prefixed_name: &str) -> Option<ErrorLevel> {
+    config
+        .rule_configurations
+        .iter()
+        .find(|rule_configuration| rule_configuration.name == plugin_prefixed_name)
+        .map(|rule_configuration| rule_configuration.level)
+}
+
+fn format_level(level: Option<ErrorLevel>) -> &'static str {
+    match level {
+        Some(ErrorLevel::Error) => "error",
+        Some(ErrorLevel::Warn) => "warn",
+        Some(ErrorLevel::Off) => "off",
+        None => "-",
+    }
+}
+
+pub fn print_rule_list(config: &Config) {
+    let rows = get_rule_list_rows(config)
+        .into_iter()
+        .map(|row| {
+            [
+                row.plugin.unwrap_or_else(|| "-".to_owned()),
+                row.name,
+                row.languages,
+                if row.fixable { "yes" } else { "no" }.to_owned(),
+                format_level(row.level).to_owned(),
+                row.description.unwrap_or_default(),
+            ]
+        })
+        .collect::<Vec<_>>();
+    let headers = [
+        "Plugin",
+        "Rule",
+        "Languages",
+        "Fixable",
+        "Level",
+        "Description",
+    ];
+    let widths = headers
+        .iter()
+        .enumerate()
+        .map(|(index, header)| {
+            rows.iter()
+                .map(|row| row[index].len())
+                .chain([header.len()])
+                .max()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let format_row = |cells: &[&str]| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, &width)| format!("{cell:width$}"))
+            .join(" | ")
+            .trim_end()
+            .to_owned()
+    };
+    println!("{}", format_row(&headers));
+    println!(
+        "{}",
+        widths.iter().map(|&width| "-".repeat(width)).join("-|-")
+    );
+    for row in &rows {
+        println!(
+            "{}",
+            format_row(&row.iter().map(|cell| &**cell).collect::<Vec<_>>())
+        );
+    }
+}
+
+pub fn get_rule_explanation(config: &Config, rule_name: &str) -> Result<String, Error> {
+    let (rule, plugin_index) = config
+        .get_rules_by_plugin_prefixed_name()
+        .get(rule_name)
+        .ok_or_else(|| Error::UnknownRule {
+            rule_name: rule_name.to_owned(),
+            location: None,
+        })?;
+    let mut markdown = get_rule_docs_markdown(
+        rule_name,
+        &rule.meta(),
+        plugin_index.map(|plugin_index| config.get_plugin_name(plugin_index)),
+    );
+    let rule_configuration = config
+        .rule_configurations
+        .iter()
+        .find(|rule_configuration| rule_configuration.name == rule_name);
+    markdown.push_str(&format!(
+        "\n\n## Configuration\n\n- Level: {}",
+        format_level(rule_configuration.map(|rule_configuration| rule_configuration.level))
+    ));
+    if let Some(options) =
+        rule_configuration.and_then(|rule_configuration| rule_configuration.options.as_ref())
+    {
+        markdown.push_str(&format!(
+            "\n\nConfigured options:\n```json\n{}\n```",
+            serde_json::to_string_pretty(options).unwrap()
+        ));
+    }
+    Ok(markdown)
+}
+
+pub(crate) fn get_rule_docs_markdown(
+    rule_name: &str,
+    meta: &RuleMeta,
+    plugin_name: Option<&str>,
+) -> String {
+    let mut markdown = match meta.docs_url.as_ref() {
+        Some(docs_url) => format!("# {rule_name}\n\n{docs_url}"),
+        None => format!("# {rule_name}"),
+    };
+    let docs = meta.docs.as_ref();
+    if let Some(description) = docs.and_then(|docs| docs.description.as_ref()) {
+        markdown.push_str(&format!("\n\n{}", trim_indent(description)));
+    }
+    markdown.push('\n');
+    if let Some(plugin_name) = plugin_name {
+        markdown.push_str(&format!("\n- Plugin: {plugin_name}"));
+    }
+    markdown.push_str(&format!(
+        "\n- Languages: {}",
+        meta.languages
+            .iter()
+            .map(|language| format!("{language:?}"))
+            .join(", ")
+    ));
+    markdown.push_str(&format!(
+        "\n- Fixable: {}",
+        if meta.fixable { "yes" } else { "no" }
+    ));
+    if !meta.tags.is_empty() {
+        markdown.push_str(&format!(
+            "\n- Tags: {}",
+            meta.tags.iter().map(|tag| format!("{tag:?}")).join(", ")
+        ));
+    }
+    if let Some(messages) = meta.messages.as_ref() {
+        markdown.push_str("\n\nMessages:\n");
+        for (message_id, message) in messages.iter().sorted_by(|(a, _), (b, _)| a.cmp(b)) {
+            markdown.push_str(&format!("\n- `{message_id}`: {message}"));
+        }
+    }
+    if let Some(options_schema) = meta.options_schema.as_ref() {
+        markdown.push_str(&format!(
+            "\n\nOptions schema:\n```json\n{}\n```",
+            serde_json::to_string_pretty(options_schema).unwrap()
+        ));
+    }
+    if let Some(docs) = docs {
+        push_examples_markdown(&mut markdown, docs);
+    }
+    markdown
+}
+
+pub(crate) fn push_examples_markdown(markdown: &mut String, docs: &RuleDocs) {
+    for (examples, fixed_outputs, label) in [
+        (&docs.examples.correct, &[][..], "correct"),
+        (
+            &docs.examples.incorrect,
+            &docs.examples.incorrect_fixed_outputs[..],
+            "incorrect",
+        ),
+    ] {
+        if examples.is_empty() {
+            continue;
+        }
+        markdown.push_str(&format!("\n\nExamples of **{label}** code:"));
+        for (index, example) in examples.iter().enumerate() {
+            markdown.push_str(&format!("\n```\n{}\n```", trim_indent(example)));
+            if let Some(Some(fixed_output)) = fixed_outputs.get(index) {
+                markdown.push_str(&format!(
+                    "\nFixed:\n```\n{}\n```",
+                    trim_indent(fixed_output)
+                ));
+            }
+        }
+    }
+}
+
+fn trim_indent(code: &str) -> String {
+    let code = code.trim_matches('\n').trim_end();
+    let indent = code
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or_default();
+    code.lines()
+        .map(|line| line.get(indent..).unwrap_or_else(|| line.trim_start()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -68,7 +68,7 @@ impl IsEmpty for Fixer {
     }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PendingFix {
     pub range: Range,
     pub replacement: String,
@@ -3,7 +3,7 @@ use std::{
     collections::HashMap,
     ops::Deref,
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{Arc, Mutex},
 };
 
 use dashmap::DashMap;
@@ -23,9 +23,14 @@ use crate::{
 
 mod accumulated_edits;
 mod fixer;
+mod review;
+mod write;
 
 pub use accumulated_edits::{get_newline_offsets_rope_or_slice, AccumulatedEdits};
 pub use fixer::{Fixer, PendingFix};
+use review::get_file_contents_bytes;
+pub(crate) use review::FixReviewer;
+pub(crate) use write::write_files;
 
 const MAX_FIX_ITERATIONS: usize = 10;
 
@@ -43,16 +48,33 @@ pub fn run_fixing_loop<'a>(
     tree: Tree,
     from_file_run_context_instance_provider_factory: &dyn FromFileRunContextInstanceProviderFactory,
     initial_run_kind: RunKind,
+    fix_reviewer: Option<&Mutex<FixReviewer>>,
 ) -> AccumulatedEdits {
     let mut file_contents = file_contents.into();
     let mut old_tree = tree;
     let mut accumulated_edits = AccumulatedEdits::new(
         get_newline_offsets_rope_or_slice(RopeOrSlice::from(&file_contents)).collect(),
     );
+    let uses_crlf = uses_crlf(&get_file_contents_bytes(&file_contents));
     for _ in 0..MAX_FIX_ITERATIONS {
         let _span = debug_span!("single fixing loop pass").entered();
 
-        let input_edits_and_replacements = apply_fixes(&mut file_contents, pending_fixes);
+        if let Some(fix_reviewer) = fix_reviewer {
+            pending_fixes = fix_reviewer.lock().unwrap().review(
+                path,
+                &get_file_contents_bytes(&file_contents),
+                pending_fixes,
+                violations,
+                config,
+            );
+            if pending_fixes.is_empty() {
+                debug!("no fixes accepted, exiting fixing loop");
+                break;
+            }
+        }
+
+        let input_edits_and_replacements =
+            apply_fixes(&mut file_contents, pending_fixes, uses_crlf);
         for (input_edit, _) in &input_edits_and_replacements {
             old_tree.edit(input_edit);
         }
@@ -152,6 +174,7 @@ pub fn run_fixing_loop<'a>(
 pub fn apply_fixes(
     file_contents: &mut MutRopeOrSlice,
     pending_fixes: HashMap<RuleName, (Vec<PendingFix>, Arc<RuleMeta>)>,
+    uses_crlf: bool,
 ) -> Vec<(InputEdit, String)> {
     let non_conflicting_sorted_pending_fixes =
         get_sorted_non_conflicting_pending_fixes(pending_fixes);
@@ -159,12 +182,52 @@ pub fn apply_fixes(
         .into_iter()
         .rev()
         .map(|PendingFix { range, replacement }| {
+            // fixes tend to be written in terms of `\n`, but one that replaced
+            // `\r\n`s presumably meant to change the line endings
+            let replacement = if uses_crlf
+                && !file_contents.range_contains_byte(range.start_byte..range.end_byte, b'\r')
+            {
+                to_crlf(replacement)
+            } else {
+                replacement
+            };
             file_contents.splice(range.start_byte..range.end_byte, &replacement);
             (get_input_edit(range, &replacement), replacement)
         })
         .collect()
 }
 
+// Only if every line ending is `\r\n`, mixed line endings get left alone
+fn uses_crlf(file_contents: &[u8]) -> bool {
+    let mut has_line_endings = false;
+    for (index, &byte) in file_contents.iter().enumerate() {
+        if byte != b'\n' {
+            continue;
+        }
+        if index == 0 || file_contents[index - 1] != b'\r' {
+            return false;
+        }
+        has_line_endings = true;
+    }
+    has_line_endings
+}
+
+fn to_crlf(replacement: String) -> String {
+    if !replacement.contains('\n') {
+        return replacement;
+    }
+    let mut converted = String::with_capacity(replacement.len());
+    let mut prev_ch = None;
+    for ch in replacement.chars() {
+        if ch == '\n' && prev_ch != Some('\r') {
+            converted.push('\r');
+        }
+        converted.push(ch);
+        prev_ch = Some(ch);
+    }
+    converted
+}
+
 fn get_updated_end_point(range: Range, replacement: &str) -> Point {
     let mut end_point: Point = range.end_point;
     for ch in replacement.chars() {
@@ -0,0 +1,260 @@
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+    io::{self, BufRead, BufReader, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use colored::Colorize;
+
+use super::PendingFix;
+use crate::{Config, MutRopeOrSlice, RuleMeta, RuleName, ViolationWithContext};
+
+// (path, plugin-prefixed rule name, (replaced text, replacement) per fix)
+type SkippedFixKey = (PathBuf, String, Vec<(Vec<u8>, String)>);
+
+enum Decision {
+    Accept,
+    Skip,
+    AcceptAllForRule,
+    Quit,
+}
+
+// Prompts for whether to apply each fix for `--fix-interactive`. Fixes are
+// attributed to the violation that they came from, which is only known when
+// `Config::fix_interactive` is set
+pub(crate) struct FixReviewer {
+    input: Box<dyn BufRead + Send>,
+    output: Box<dyn Write + Send>,
+    accepted_rules: HashSet<String>,
+    // the fixing loop re-runs the rules after each round of fixes, so a
+    // skipped fix will likely get proposed again
+    skipped: HashSet<SkippedFixKey>,
+    has_quit: bool,
+}
+
+impl FixReviewer {
+    pub(crate) fn new(input: Box<dyn BufRead + Send>, output: Box<dyn Write + Send>) -> Self {
+        Self {
+            input,
+            output,
+            accepted_rules: Default::default(),
+            skipped: Default::default(),
+            has_quit: false,
+        }
+    }
+
+    // stdout is reserved for the formatted violations
+    pub(crate) fn for_terminal() -> Self {
+        Self::new(
+            Box::new(BufReader::new(io::stdin())),
+            Box::new(io::stderr()),
+        )
+    }
+
+    // Returns the accepted subset of `pending_fixes`. Violations whose fixes
+    // weren't accepted no longer count as fixed
+    pub(crate) fn review(
+        &mut self,
+        path: &Path,
+        file_contents: &[u8],
+        mut pending_fixes: HashMap<RuleName, (Vec<PendingFix>, Arc<RuleMeta>)>,
+        violations: &mut [ViolationWithContext],
+        config: &Config,
+    ) -> HashMap<RuleName, (Vec<PendingFix>, Arc<RuleMeta>)> {
+        let mut accepted_fixes: HashMap<RuleName, Vec<PendingFix>> = Default::default();
+        let mut violation_indices = (0..violations.len()).collect::<Vec<_>>();
+        violation_indices.sort_by_key(|&index| violations[index].range.start_byte);
+        for index in violation_indices {
+            let violation = &mut violations[index];
+            if !violation.had_fixes {
+                continue;
+            }
+            let Some(fixes) = violation.fixes.as_ref() else {
+                continue;
+            };
+            let Some((rule_pending_fixes, _)) = pending_fixes.get_mut(&violation.rule.name) else {
+                continue;
+            };
+            // eg a violation left over from an earlier round of fixes
+            if !fixes.iter().all(|fix| rule_pending_fixes.contains(fix)) {
+                continue;
+            }
+            rule_pending_fixes.retain(|pending_fix| !fixes.contains(pending_fix));
+
+            if self.should_apply(path, file_contents, violation, fixes, config) {
+                accepted_fixes
+                    .entry(violation.rule.name.clone())
+                    .or_default()
+                    .extend(fixes.iter().cloned());
+            } else {
+                violation.had_fixes = false;
+            }
+        }
+        pending_fixes
+            .into_iter()
+            .filter_map(|(rule_name, (_, rule_meta))| {
+                accepted_fixes
+                    .remove(&rule_name)
+                    .map(|fixes| (rule_name, (fixes, rule_meta)))
+            })
+            .collect()
+    }
+
+    fn should_apply(
+        &mut self,
+        path: &Path,
+        file_contents: &[u8],
+        violation: &ViolationWithContext,
+        fixes: &[PendingFix],
+        config: &Config,
+    ) -> bool {
+        if self.has_quit {
+            return false;
+        }
+        let rule_name = violation.plugin_prefixed_rule_name(config);
+        if self.accepted_rules.contains(&rule_name) {
+            return true;
+        }
+        let skipped_fix_key: SkippedFixKey = (
+            path.to_owned(),
+            rule_name.clone(),
+            fixes
+                .iter()
+                .map(|fix| {
+                    (
+                        file_contents[fix.range.start_byte..fix.range.end_byte].to_owned(),
+                        fix.replacement.clone(),
+                    )
+                })
+                .collect(),
+        );
+        if self.skipped.contains(&skipped_fix_key) {
+            return false;
+        }
+
+        let _ = write!(
+            self.output,
+            "{}",
+            format_fix_hunk(path, file_contents, violation, fixes, &rule_name)
+        );
+        match self.prompt() {
+            Decision::Accept => true,
+            Decision::AcceptAllForRule => {
+                self.accepted_rules.insert(rule_name);
+                true
+            }
+            Decision::Skip => {
+                self.skipped.insert(skipped_fix_key);
+                false
+            }
+            Decision::Quit => {
+                self.has_quit = true;
+                false
+            }
+        }
+    }
+
+    fn prompt(&mut self) -> Decision {
+        loop {
+            let _ = write!(
+                self.output,
+                "Apply this fix? [y]es, [n]o, [a]ll for this rule, [q]uit: "
+            );
+            let _ = self.output.flush();
+            let mut answer = String::new();
+            match self.input.read_line(&mut answer) {
+                Ok(0) | Err(_) => return Decision::Quit,
+                Ok(_) => {}
+            }
+            let _ = writeln!(self.output);
+            match &*answer.trim().to_lowercase() {
+                "y" | "yes" => return Decision::Accept,
+                "n" | "no" => return Decision::Skip,
+                "a" | "all" => return Decision::AcceptAllForRule,
+                "q" | "quit" => return Decision::Quit,
+                _ => {}
+            }
+        }
+    }
+}
+
+// The lines touched by the fixes, before and after
+fn format_fix_hunk(
+    path: &Path,
+    file_contents: &[u8],
+    violation: &ViolationWithContext,
+    fixes: &[PendingFix],
+    rule_name: &str,
+) -> String {
+    let start_byte = fixes.iter().map(|fix| fix.range.start_byte).min().unwrap();
+    let end_byte = fixes.iter().map(|fix| fix.range.end_byte).max().unwrap();
+    let hunk_start_byte = file_contents[..start_byte]
+        .iter()
+        .rposition(|&byte| byte == b'\n')
+        .map_or(0, |newline_offset| newline_offset + 1);
+    let hunk_end_byte = file_contents[end_byte..]
+        .iter()
+        .position(|&byte| byte == b'\n')
+        .map_or(file_contents.len(), |newline_offset| {
+            end_byte + newline_offset
+        });
+    let old_text = &file_contents[hunk_start_byte..hunk_end_byte];
+    let mut new_text = old_text.to_owned();
+    let mut fixes = fixes.iter().collect::<Vec<_>>();
+    fixes.sort_by_key(|fix| fix.range.start_byte);
+    for fix in fixes.into_iter().rev() {
+        new_text.splice(
+            fix.range.start_byte - hunk_start_byte..fix.range.end_byte - hunk_start_byte,
+            fix.replacement.bytes(),
+        );
+    }
+    let old_lines = String::from_utf8_lossy(old_text)
+        .split('\n')
+        .map(ToOwned::to_owned)
+        .collect::<Vec<_>>();
+    let new_lines = String::from_utf8_lossy(&new_text)
+        .split('\n')
+        .map(ToOwned::to_owned)
+        .collect::<Vec<_>>();
+    let first_line = file_contents[..hunk_start_byte]
+        .iter()
+        .filter(|&&byte| byte == b'\n')
+        .count()
+        + 1;
+
+    let mut output = format!(
+        "{}:{}:{}  {}  {}\n",
+        path.display().to_string().underline(),
+        violation.range.start_point.row + 1,
+        violation.range.start_point.column + 1,
+        violation.message().bold(),
+        rule_name.dimmed(),
+    );
+    output.push_str(
+        &format!(
+            "@@ -{first_line},{} +{first_line},{} @@\n",
+            old_lines.len(),
+            new_lines.len()
+        )
+        .cyan()
+        .to_string(),
+    );
+    for line in &old_lines {
+        output.push_str(&format!("-{line}").red().to_string());
+        output.push('\n');
+    }
+    for line in &new_lines {
+        output.push_str(&format!("+{line}").green().to_string());
+        output.push('\n');
+    }
+    output
+}
+
+pub(crate) fn get_file_contents_bytes<'a>(file_contents: &'a MutRopeOrSlice) -> Cow<'a, [u8]> {
+    match file_contents {
+        MutRopeOrSlice::Rope(rope) => Cow::Owned(rope.to_string().into_bytes()),
+        MutRopeOrSlice::Slice(slice) => Cow::Borrowed(slice),
+    }
+}
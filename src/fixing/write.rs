@@ -0,0 +1,65 @@
+use std::{
+    ffi::OsString,
+    fs::{self, File},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+use tracing::{debug_span, instrument};
+
+use crate::FixWriteFailure;
+
+// Keeps going after a failure so that one unwritable file doesn't prevent
+// fixing the rest
+#[instrument(level = "debug", skip_all)]
+pub(crate) fn write_files<'a>(
+    files_to_write: impl Iterator<Item = (&'a Path, &'a [u8])>,
+) -> Vec<FixWriteFailure> {
+    files_to_write
+        .filter_map(|(path, file_contents)| {
+            let _span = debug_span!("write file", ?path).entered();
+
+            write_file(path, file_contents)
+                .err()
+                .map(|source| FixWriteFailure {
+                    path: path.to_owned(),
+                    source,
+                })
+        })
+        .collect()
+}
+
+// Writes to a temporary file alongside the original and then renames it into
+// place, so that a crash can't leave behind a truncated file
+fn write_file(path: &Path, file_contents: &[u8]) -> io::Result<()> {
+    // so that a symlink gets its target updated rather than replaced
+    let path = fs::canonicalize(path)?;
+    let permissions = fs::metadata(&path)?.permissions();
+    if permissions.readonly() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "file is read-only",
+        ));
+    }
+
+    let temp_path = get_temp_path(&path);
+    let result = (|| {
+        let mut file = File::create(&temp_path)?;
+        file.write_all(file_contents)?;
+        file.set_permissions(permissions)?;
+        file.sync_all()?;
+        fs::rename(&temp_path, &path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result
+}
+
+fn get_temp_path(path: &Path) -> PathBuf {
+    let mut file_name = OsString::from(".");
+    file_name.push(path.file_name().unwrap());
+    file_name.push(format!(".tree-sitter-lint-{}.tmp", process::id()));
+    path.with_file_name(file_name)
+}
@@ -0,0 +1,47 @@
+use std::{fmt::Write, path::Path};
+
+use super::{escape_xml, group_by_path, Formatter};
+use crate::{Config, ViolationWithContext};
+
+pub(super) struct CheckstyleFormatter;
+
+impl Formatter for CheckstyleFormatter {
+    fn format(
+        &self,
+        violations: &[ViolationWithContext],
+        config: &Config,
+        _get_file_contents: &dyn Fn(&Path) -> Option<Vec<u8>>,
+    ) -> String {
+        let mut output = String::new();
+        output.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        output.push_str("<checkstyle version=\"4.3\">\n");
+        for (path, violations) in group_by_path(violations) {
+            writeln!(
+                output,
+                "  <file name=\"{}\">",
+                escape_xml(&path.to_string_lossy())
+            )
+            .unwrap();
+            for violation in violations {
+                writeln!(
+                    output,
+                    "    <error line=\"{}\" column=\"{}\" severity=\"{}\" message=\"{}\" \
+                     source=\"{}\" />",
+                    violation.range.start_point.row + 1,
+                    violation.range.start_point.column + 1,
+                    if violation.is_warning() {
+                        "warning"
+                    } else {
+                        "error"
+                    },
+                    escape_xml(&violation.message()),
+                    escape_xml(&violation.plugin_prefixed_rule_name(config)),
+                )
+                .unwrap();
+            }
+            output.push_str("  </file>\n");
+        }
+        output.push_str("</checkstyle>\n");
+        output
+    }
+}
@@ -0,0 +1,20 @@
+use std::path::Path;
+
+use super::Formatter;
+use crate::{Config, ViolationWithContext};
+
+pub(super) struct CompactFormatter;
+
+impl Formatter for CompactFormatter {
+    fn format(
+        &self,
+        violations: &[ViolationWithContext],
+        config: &Config,
+        _get_file_contents: &dyn Fn(&Path) -> Option<Vec<u8>>,
+    ) -> String {
+        violations
+            .iter()
+            .map(|violation| format!("{}\n", violation.format(config)))
+            .collect()
+    }
+}
@@ -0,0 +1,47 @@
+use std::path::Path;
+
+use super::Formatter;
+use crate::{Config, ViolationWithContext};
+
+// https://docs.github.com/en/actions/using-workflows/workflow-commands-for-github-actions#setting-an-error-message
+pub(super) struct GithubFormatter;
+
+impl Formatter for GithubFormatter {
+    fn format(
+        &self,
+        violations: &[ViolationWithContext],
+        config: &Config,
+        _get_file_contents: &dyn Fn(&Path) -> Option<Vec<u8>>,
+    ) -> String {
+        violations
+            .iter()
+            .map(|violation| {
+                format!(
+                    "::{} file={},line={},col={},endLine={},endColumn={},title={}::{}\n",
+                    if violation.is_warning() {
+                        "warning"
+                    } else {
+                        "error"
+                    },
+                    escape_property(&violation.path.to_string_lossy()),
+                    violation.range.start_point.row + 1,
+                    violation.range.start_point.column + 1,
+                    violation.range.end_point.row + 1,
+                    violation.range.end_point.column + 1,
+                    escape_property(&violation.plugin_prefixed_rule_name(config)),
+                    escape_data(&violation.message()),
+                )
+            })
+            .collect()
+    }
+}
+
+fn escape_data(text: &str) -> String {
+    text.replace('%', "%25")
+        .replace('\r', "%0D")
+        .replace('\n', "%0A")
+}
+
+fn escape_property(text: &str) -> String {
+    escape_data(text).replace(':', "%3A").replace(',', "%2C")
+}
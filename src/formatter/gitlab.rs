@@ -0,0 +1,51 @@
+use std::path::Path;
+
+use serde_json::json;
+
+use super::Formatter;
+use crate::{baseline::get_fingerprint, Config, ViolationWithContext};
+
+// https://docs.gitlab.com/ee/ci/testing/code_quality.html#implement-a-custom-tool
+pub(super) struct GitlabFormatter;
+
+impl Formatter for GitlabFormatter {
+    fn format(
+        &self,
+        violations: &[ViolationWithContext],
+        config: &Config,
+        _get_file_contents: &dyn Fn(&Path) -> Option<Vec<u8>>,
+    ) -> String {
+        let issues = violations
+            .iter()
+            .map(|violation| {
+                let rule_name = violation.plugin_prefixed_rule_name(config);
+                let message = violation.message();
+                let path = violation.path.to_string_lossy();
+                json!({
+                    "description": message,
+                    "check_name": rule_name,
+                    // GitLab uses this to track issues across pipelines, so
+                    // (like baseline entries) it's not based on line numbers
+                    "fingerprint": format!(
+                        "{:016x}",
+                        get_fingerprint(&format!(
+                            "{path}:{rule_name}:{message}:{:016x}",
+                            violation.fingerprint
+                        ))
+                    ),
+                    "severity": if violation.is_warning() { "minor" } else { "major" },
+                    "location": {
+                        "path": path,
+                        "lines": {
+                            "begin": violation.range.start_point.row + 1,
+                            "end": violation.range.end_point.row + 1,
+                        },
+                    },
+                })
+            })
+            .collect::<Vec<_>>();
+        let mut output = serde_json::to_string_pretty(&issues).unwrap();
+        output.push('\n');
+        output
+    }
+}
@@ -0,0 +1,62 @@
+use std::{fmt::Write, path::Path};
+
+use super::{escape_xml, group_by_path, Formatter};
+use crate::{Config, ViolationWithContext};
+
+pub(super) struct JunitFormatter;
+
+// One test suite per file and one (failed) test case per violation
+impl Formatter for JunitFormatter {
+    fn format(
+        &self,
+        violations: &[ViolationWithContext],
+        config: &Config,
+        _get_file_contents: &dyn Fn(&Path) -> Option<Vec<u8>>,
+    ) -> String {
+        let mut output = String::new();
+        output.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        output.push_str("<testsuites>\n");
+        for (path, violations) in group_by_path(violations) {
+            let path = escape_xml(&path.to_string_lossy());
+            writeln!(
+                output,
+                "  <testsuite package=\"tree-sitter-lint\" time=\"0\" tests=\"{}\" errors=\"{}\" \
+                 name=\"{path}\">",
+                violations.len(),
+                violations.len(),
+            )
+            .unwrap();
+            for violation in violations {
+                let rule_name = violation.plugin_prefixed_rule_name(config);
+                let message = violation.message();
+                writeln!(
+                    output,
+                    "    <testcase time=\"0\" name=\"{}\" classname=\"{path}\">",
+                    escape_xml(&rule_name),
+                )
+                .unwrap();
+                writeln!(
+                    output,
+                    "      <failure message=\"{}\"><![CDATA[line {}, col {}, {} - {} \
+                     ({})]]></failure>",
+                    escape_xml(&message),
+                    violation.range.start_point.row + 1,
+                    violation.range.start_point.column + 1,
+                    if violation.is_warning() {
+                        "Warning"
+                    } else {
+                        "Error"
+                    },
+                    // the only thing that can't appear in a CDATA section
+                    message.replace("]]>", "]]]]><![CDATA[>"),
+                    rule_name,
+                )
+                .unwrap();
+                output.push_str("    </testcase>\n");
+            }
+            output.push_str("  </testsuite>\n");
+        }
+        output.push_str("</testsuites>\n");
+        output
+    }
+}
@@ -0,0 +1,76 @@
+mod checkstyle;
+mod compact;
+mod github;
+mod gitlab;
+mod junit;
+mod stylish;
+
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+use crate::{Config, Error, ViolationWithContext};
+
+pub(crate) const DEFAULT_FORMAT: &str = "stylish";
+
+pub trait Formatter: Send + Sync {
+    // `get_file_contents()` returns the current (ie possibly fixed) contents
+    // of a linted file
+    fn format(
+        &self,
+        violations: &[ViolationWithContext],
+        config: &Config,
+        get_file_contents: &dyn Fn(&Path) -> Option<Vec<u8>>,
+    ) -> String;
+}
+
+pub(crate) fn get_builtin_formatter(name: &str) -> Option<Arc<dyn Formatter>> {
+    Some(match name {
+        "stylish" => Arc::new(stylish::StylishFormatter),
+        "compact" => Arc::new(compact::CompactFormatter),
+        "checkstyle" => Arc::new(checkstyle::CheckstyleFormatter),
+        "junit" => Arc::new(junit::JunitFormatter),
+        "github" => Arc::new(github::GithubFormatter),
+        "gitlab" => Arc::new(gitlab::GitlabFormatter),
+        _ => return None,
+    })
+}
+
+pub(crate) fn format_violations(
+    violations: &[ViolationWithContext],
+    config: &Config,
+    get_file_contents: impl Fn(&Path) -> Option<Vec<u8>>,
+) -> Result<String, Error> {
+    Ok(config
+        .get_formatter()?
+        .format(violations, config, &get_file_contents))
+}
+
+fn group_by_path(
+    violations: &[ViolationWithContext],
+) -> BTreeMap<&Path, Vec<&ViolationWithContext>> {
+    let mut violations_by_path: BTreeMap<&Path, Vec<&ViolationWithContext>> = Default::default();
+    for violation in violations {
+        violations_by_path
+            .entry(&violation.path)
+            .or_default()
+            .push(violation);
+    }
+    for violations in violations_by_path.values_mut() {
+        violations.sort_by_key(|violation| violation.range.start_byte);
+    }
+    violations_by_path
+}
+
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        match ch {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            ch => escaped.push(ch),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,166 @@
+use std::{fmt::Write, path::Path};
+
+use colored::Colorize;
+
+use super::{group_by_path, Formatter};
+use crate::{tree_sitter::Range, Config, ViolationWithContext};
+
+const CODE_FRAME_CONTEXT_LINES: usize = 1;
+
+pub(super) struct StylishFormatter;
+
+impl Formatter for StylishFormatter {
+    fn format(
+        &self,
+        violations: &[ViolationWithContext],
+        config: &Config,
+        get_file_contents: &dyn Fn(&Path) -> Option<Vec<u8>>,
+    ) -> String {
+        if violations.is_empty() {
+            return Default::default();
+        }
+        let num_violations = violations.len();
+        let num_warnings = violations
+            .iter()
+            .filter(|violation| violation.is_warning())
+            .count();
+        let num_errors = num_violations - num_warnings;
+        let num_fixable = violations
+            .iter()
+            .filter(|violation| violation.has_fix && violation.rule.fixable)
+            .count();
+
+        let mut output = String::new();
+        for (path, violations) in group_by_path(violations) {
+            let file_contents = config.code_frame.then(|| get_file_contents(path)).flatten();
+            let file_contents = file_contents
+                .as_ref()
+                .map(|file_contents| String::from_utf8_lossy(file_contents));
+            let lines = file_contents
+                .as_ref()
+                .map(|file_contents| file_contents.lines().collect::<Vec<_>>());
+            let positions = violations
+                .iter()
+                .map(|violation| {
+                    format!(
+                        "{}:{}",
+                        violation.range.start_point.row + 1,
+                        violation.range.start_point.column + 1
+                    )
+                })
+                .collect::<Vec<_>>();
+            let messages = violations
+                .iter()
+                .map(|violation| violation.message())
+                .collect::<Vec<_>>();
+            let position_width = positions
+                .iter()
+                .map(|position| position.len())
+                .max()
+                .unwrap();
+            let severity_width = if violations.iter().any(|violation| violation.is_warning()) {
+                "warning".len()
+            } else {
+                "error".len()
+            };
+            let message_width = messages
+                .iter()
+                .map(|message| message.chars().count())
+                .max()
+                .unwrap();
+
+            writeln!(output, "{}", path.display().to_string().underline()).unwrap();
+            for ((violation, position), message) in violations.iter().zip(&positions).zip(&messages)
+            {
+                writeln!(
+                    output,
+                    "  {}  {}  {}  {}",
+                    format!("{position:<position_width$}").dimmed(),
+                    if violation.is_warning() {
+                        format!("{:<severity_width$}", "warning").yellow()
+                    } else {
+                        format!("{:<severity_width$}", "error").red()
+                    },
+                    format!("{message:<message_width$}").bold(),
+                    violation.plugin_prefixed_rule_name(config).dimmed(),
+                )
+                .unwrap();
+                if let Some(lines) = lines.as_ref() {
+                    output.push_str(&format_code_frame(lines, violation.range));
+                }
+            }
+            output.push('\n');
+        }
+
+        let mut summary = format!(
+            "✖ {num_violations} problem{}",
+            if num_violations == 1 { "" } else { "s" }
+        );
+        let mut details: Vec<String> = Default::default();
+        if num_warnings > 0 {
+            details.push(format!(
+                "{num_errors} error{}, {num_warnings} warning{}",
+                if num_errors == 1 { "" } else { "s" },
+                if num_warnings == 1 { "" } else { "s" }
+            ));
+        }
+        if num_fixable > 0 {
+            details.push(format!("{num_fixable} fixable with --fix"));
+        }
+        if !details.is_empty() {
+            write!(summary, " ({})", details.join("; ")).unwrap();
+        }
+        let summary = if num_errors > 0 {
+            summary.red()
+        } else {
+            summary.yellow()
+        };
+        writeln!(output, "{}", summary.bold()).unwrap();
+        output
+    }
+}
+
+fn format_code_frame(lines: &[&str], range: Range) -> String {
+    let row = range.start_point.row;
+    let Some(line) = lines.get(row) else {
+        return Default::default();
+    };
+    let first_row = row.saturating_sub(CODE_FRAME_CONTEXT_LINES);
+    let last_row = (row + CODE_FRAME_CONTEXT_LINES).min(lines.len() - 1);
+    let gutter_width = (last_row + 1).to_string().len();
+
+    let mut output = String::new();
+    for (context_row, context_line) in lines.iter().enumerate().take(last_row + 1).skip(first_row) {
+        let formatted_line = format!(
+            "    {} {} {context_line}",
+            if context_row == row { ">" } else { " " },
+            format!("{:>gutter_width$} |", context_row + 1).dimmed(),
+        );
+        writeln!(output, "{}", formatted_line.trim_end()).unwrap();
+        if context_row == row {
+            // tree-sitter columns are byte offsets
+            let start_column = range.start_point.column.min(line.len());
+            let end_column = if range.end_point.row == row {
+                range.end_point.column.clamp(start_column, line.len())
+            } else {
+                line.len()
+            };
+            let leading_whitespace = String::from_utf8_lossy(&line.as_bytes()[..start_column])
+                .chars()
+                .map(|ch| if ch == '\t' { '\t' } else { ' ' })
+                .collect::<String>();
+            let num_carets = String::from_utf8_lossy(&line.as_bytes()[start_column..end_column])
+                .chars()
+                .count()
+                .max(1);
+            writeln!(
+                output,
+                "      {} {leading_whitespace}{}",
+                format!("{:>gutter_width$} |", "").dimmed(),
+                "^".repeat(num_carets).red(),
+            )
+            .unwrap();
+        }
+    }
+    output
+}
@@ -0,0 +1,170 @@
+use std::{
+    collections::HashMap,
+    ops::RangeInclusive,
+    path::{Component, Path, PathBuf},
+    process::Command,
+};
+
+use tracing::instrument;
+
+use crate::{Config, Error, ViolationWithContext};
+
+pub(crate) struct ChangedFiles {
+    // 1-based, inclusive ranges of added or modified lines
+    pub changed_lines: HashMap<PathBuf, Vec<RangeInclusive<usize>>>,
+}
+
+impl ChangedFiles {
+    // None unless --changed-since or --staged was passed
+    #[instrument(level = "debug", skip_all)]
+    pub fn for_config(config: &Config) -> Result<Option<Self>, Error> {
+        let mut args = vec![
+            "diff",
+            "-U0",
+            "--relative",
+            "--no-prefix",
+            "--no-color",
+            "--no-ext-diff",
+            "--diff-filter=ACMR",
+        ];
+        match (config.changed_since.as_deref(), config.staged) {
+            // so that eg a ref named `--output=whee` can't get treated as an
+            // option
+            (Some(changed_since), _) => args.extend(["--end-of-options", changed_since]),
+            (None, true) => args.push("--cached"),
+            (None, false) => return Ok(None),
+        }
+        let diff = run_git(&args)?;
+        let mut changed_lines = parse_diff(&String::from_utf8_lossy(&diff));
+        if !config.paths.is_empty() {
+            changed_lines.retain(|path, _| {
+                config
+                    .paths
+                    .iter()
+                    .any(|config_path| normalize(path).starts_with(normalize(config_path)))
+            });
+        }
+        Ok(Some(Self { changed_lines }))
+    }
+
+    pub fn paths(&self) -> Vec<PathBuf> {
+        let mut paths = self.changed_lines.keys().cloned().collect::<Vec<_>>();
+        paths.sort();
+        paths
+    }
+
+    pub fn contains_violation(&self, violation: &ViolationWithContext) -> bool {
+        let start_line = violation.range.start_point.row + 1;
+        let end_line = violation.range.end_point.row + 1;
+        self.changed_lines
+            .get(&normalize(&violation.path))
+            .is_some_and(|ranges| {
+                ranges
+                    .iter()
+                    .any(|range| start_line <= *range.end() && end_line >= *range.start())
+            })
+    }
+}
+
+// Expects the output of `git diff -U0 --no-prefix`
+pub(crate) fn parse_diff(diff: &str) -> HashMap<PathBuf, Vec<RangeInclusive<usize>>> {
+    let mut changed_lines: HashMap<PathBuf, Vec<RangeInclusive<usize>>> = Default::default();
+    let mut current_path: Option<PathBuf> = Default::default();
+    for line in diff.lines() {
+        if let Some(path) = line.strip_prefix("+++ ") {
+            current_path = (path != "/dev/null").then(|| parse_diff_path(path));
+            if let Some(current_path) = current_path.as_ref() {
+                changed_lines.entry(current_path.clone()).or_default();
+            }
+        } else if let Some(hunk_header) = line.strip_prefix("@@ ") {
+            let (Some(current_path), Some(range)) =
+                (current_path.as_ref(), parse_hunk_header(hunk_header))
+            else {
+                continue;
+            };
+            changed_lines.get_mut(current_path).unwrap().push(range);
+        }
+    }
+    changed_lines
+}
+
+// Even with `core.quotePath=false`, paths containing eg quotes or control
+// characters get C-quoted. And ones containing spaces get a trailing tab
+fn parse_diff_path(path: &str) -> PathBuf {
+    let path = path.strip_suffix('\t').unwrap_or(path);
+    let Some(quoted) = path
+        .strip_prefix('"')
+        .and_then(|path| path.strip_suffix('"'))
+    else {
+        return PathBuf::from(path);
+    };
+    let mut unquoted: Vec<u8> = Vec::with_capacity(quoted.len());
+    let mut bytes = quoted.bytes();
+    while let Some(byte) = bytes.next() {
+        if byte != b'\\' {
+            unquoted.push(byte);
+            continue;
+        }
+        let Some(escaped) = bytes.next() else {
+            break;
+        };
+        unquoted.push(match escaped {
+            b'a' => 0x07,
+            b'b' => 0x08,
+            b'f' => 0x0c,
+            b'n' => b'\n',
+            b'r' => b'\r',
+            b't' => b'\t',
+            b'v' => 0x0b,
+            // eg `\303\251` for the UTF-8 bytes of a non-ASCII character
+            b'0'..=b'7' => bytes
+                .by_ref()
+                .take(2)
+                .fold(escaped - b'0', |value, digit| value * 8 + (digit - b'0')),
+            escaped => escaped,
+        });
+    }
+    PathBuf::from(String::from_utf8_lossy(&unquoted).into_owned())
+}
+
+// eg "-12,3 +14,2 @@ fn whee() {"
+fn parse_hunk_header(hunk_header: &str) -> Option<RangeInclusive<usize>> {
+    let new_range = hunk_header
+        .split(' ')
+        .find_map(|chunk| chunk.strip_prefix('+'))?;
+    let (start, count) = match new_range.split_once(',') {
+        Some((start, count)) => (start.parse::<usize>().ok()?, count.parse::<usize>().ok()?),
+        None => (new_range.parse::<usize>().ok()?, 1),
+    };
+    // pure deletions don't leave any lines behind
+    (count > 0).then(|| start..=start + count - 1)
+}
+
+pub(crate) fn get_staged_file_contents(path: &Path) -> Result<Vec<u8>, Error> {
+    run_git(&["show", &format!(":./{}", normalize(path).display())])
+}
+
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    path.components()
+        .filter(|component| *component != Component::CurDir)
+        .collect()
+}
+
+fn run_git(args: &[&str]) -> Result<Vec<u8>, Error> {
+    let git_error = |message: String| Error::Git {
+        command: args.join(" "),
+        message,
+    };
+    let output = Command::new("git")
+        // otherwise non-ASCII paths come back C-quoted
+        .args(["-c", "core.quotePath=false"])
+        .args(args)
+        .output()
+        .map_err(|error| git_error(error.to_string()))?;
+    if !output.status.success() {
+        return Err(git_error(
+            String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        ));
+    }
+    Ok(output.stdout)
+}
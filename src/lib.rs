@@ -1,38 +1,55 @@
 #![allow(clippy::into_iter_on_ref)]
 
 mod aggregated_queries;
+mod baseline;
 mod cli;
 mod config;
 mod configuration;
 mod context;
+#[cfg(unix)]
+mod daemon;
+mod docs;
 mod environment;
+mod error;
+mod explain;
 mod fixing;
+mod formatter;
+mod git;
 pub mod lsp;
 mod macros;
 mod node;
 mod plugin;
 mod rule;
 mod rule_tester;
+mod schema;
 mod slice;
 #[cfg(test)]
 mod tests;
 mod text;
+mod timing;
+mod trace;
 mod treesitter;
 mod violation;
 mod visit;
+mod watch;
 
 use std::{
     collections::HashMap,
     fmt, fs,
+    io::{self, Read, Write},
+    mem,
+    panic::{self, AssertUnwindSafe},
     path::{Path, PathBuf},
-    sync::{Arc, Mutex},
+    sync::{Arc, Mutex, OnceLock},
 };
 
 use aggregated_queries::AggregatedQueries;
+use baseline::apply_baseline;
+use clap::ValueEnum;
 pub use cli::bootstrap_cli;
 pub use config::{
-    Args, ArgsBuilder, Config, ConfigBuilder, ErrorLevel, RuleConfiguration,
-    RuleConfigurationValue, RuleConfigurationValueBuilder,
+    Args, ArgsBuilder, Command, Config, ConfigBuilder, ErrorLevel, RuleConfiguration,
+    RuleConfigurationOrigin, RuleConfigurationValue, RuleConfigurationValueBuilder,
 };
 pub use configuration::{Configuration, ConfigurationBuilder};
 use context::get_node_parent_cache;
@@ -43,27 +60,42 @@ pub use context::{
     NodeParentCache, NodeParentProvider, QueryMatchContext, RunKind, SkipOptions,
     SkipOptionsBuilder, StandaloneNodeParentProvider,
 };
+#[cfg(unix)]
+pub use daemon::run_daemon;
 use dashmap::DashMap;
-use fixing::{run_fixing_loop, AllPendingFixes, PendingFix, PerFilePendingFixes};
-pub use fixing::{AccumulatedEdits, Fixer};
+pub use docs::generate_rule_docs;
+pub use error::{Error, FixWriteFailure, SourceLocation};
+pub use explain::{get_rule_explanation, print_rule_list};
+use fixing::{run_fixing_loop, write_files, AllPendingFixes, FixReviewer, PerFilePendingFixes};
+pub use fixing::{AccumulatedEdits, Fixer, PendingFix};
+use formatter::format_violations;
+pub use formatter::Formatter;
+use git::{get_staged_file_contents, ChangedFiles};
+use ignore::{
+    types::{Types, TypesBuilder},
+    Match,
+};
 use maybe_owned::MaybeOwned;
 pub use node::{compare_nodes, NodeExt, NonCommentChildren};
 use ouroboros::self_referencing;
 pub use plugin::{Plugin, PluginBuilder};
 pub use proc_macros::{builder_args, instance_provider_factory, rule, rule_tests, violation};
 use rayon::prelude::*;
-use rule::{Captures, InstantiatedRule};
 pub use rule::{
-    MatchBy, NodeOrCaptures, Rule, RuleInstance, RuleInstancePerFile, RuleListenerQuery, RuleMeta,
+    validate_rule_options, MatchBy, NodeOrCaptures, Rule, RuleDocs, RuleExamples, RuleInstance,
+    RuleInstancePerFile, RuleListenerQuery, RuleMeta, RuleOptions, RuleOptionsError, RuleTag,
 };
+use rule::{Captures, InstantiatedRule};
 pub use rule_tester::{
     DummyFromFileRunContextInstanceProviderFactory, RuleTestExpectedError,
     RuleTestExpectedErrorBuilder, RuleTestExpectedOutput, RuleTestInvalid, RuleTestInvalidBuilder,
     RuleTestValid, RuleTestValidBuilder, RuleTester, RuleTests,
 };
+pub use schema::get_config_schema;
 pub use slice::MutRopeOrSlice;
-use squalid::EverythingExt;
 pub use text::SourceTextProvider;
+pub use timing::{Timings, TIMING_ENV_VAR_NAME};
+pub use trace::{get_trace_file_path, init_cli_tracing, init_lsp_tracing, LOG_ENV_VAR_NAME};
 use tracing::{debug, debug_span, info_span, instrument, trace};
 use tree_sitter::Tree;
 use tree_sitter_grep::{
@@ -76,8 +108,9 @@ pub use treesitter::{
     range_between_end_and_start, range_between_ends, range_between_start_and_end,
     range_between_starts,
 };
-pub use violation::{ViolationBuilder, ViolationData, ViolationWithContext};
+pub use violation::{RelatedLocation, ViolationBuilder, ViolationData, ViolationWithContext};
 pub use visit::{walk_tree, TreeEnterLeaveVisitor};
+pub use watch::run_watch;
 
 pub extern crate better_any;
 pub extern crate clap;
@@ -92,6 +125,28 @@ pub use tree_sitter_grep::{ropey, tree_sitter};
 pub enum ExitStatus {
     Ok,
     FoundViolations,
+    SetupError,
+    InternalError,
+    FixWriteError,
+}
+
+impl ExitStatus {
+    pub fn code(&self) -> i32 {
+        match self {
+            Self::Ok => 0,
+            Self::FoundViolations => 1,
+            Self::SetupError => 2,
+            Self::InternalError => 3,
+            Self::FixWriteError => 4,
+        }
+    }
+
+    pub fn for_error(error: &Error) -> Self {
+        match error {
+            Error::FixWrite { .. } => Self::FixWriteError,
+            _ => Self::SetupError,
+        }
+    }
 }
 
 #[instrument(skip_all)]
@@ -99,30 +154,514 @@ pub fn run_and_output(
     config: Config,
     from_file_run_context_instance_provider_factory: &dyn FromFileRunContextInstanceProviderFactory,
 ) -> ExitStatus {
-    let violations = run(&config, from_file_run_context_instance_provider_factory);
-    if violations.is_empty() {
+    if let Some(command) = config.command.as_ref() {
+        return run_command(command, &config);
+    }
+    if let Some(path) = config.print_config.as_ref() {
+        let effective_config = match path {
+            Some(path) => match config.get_effective_config_for_file(path) {
+                Ok(effective_config) => effective_config,
+                Err(error) => {
+                    print_error(&error);
+                    return ExitStatus::SetupError;
+                }
+            },
+            None => config.get_effective_config(None),
+        };
+        println!(
+            "{}",
+            serde_json::to_string_pretty(&effective_config).unwrap()
+        );
         return ExitStatus::Ok;
     }
+    let formatted_run_status = match config.stdin_filename.as_ref() {
+        Some(stdin_filename) => read_stdin().and_then(|file_contents| {
+            run_and_format_for_stdin(
+                &config,
+                from_file_run_context_instance_provider_factory,
+                stdin_filename,
+                file_contents,
+            )
+            .map(|formatted_stdin_run_status| {
+                (
+                    formatted_stdin_run_status.formatted_run_status,
+                    formatted_stdin_run_status.fixed_file_contents,
+                )
+            })
+        }),
+        None => run(&config, from_file_run_context_instance_provider_factory).map(|run_status| {
+            let _span = info_span!(
+                "formatting violations",
+                num_violations = run_status.violations.len()
+            )
+            .entered();
+            (
+                format_run_status(&config, run_status, |path| fs::read(path).ok()),
+                None,
+            )
+        }),
+    };
+    let (
+        FormattedRunStatus {
+            stdout: output,
+            stderr,
+            exit_status,
+        },
+        fixed_file_contents,
+    ) = match formatted_run_status {
+        Ok(formatted_run_status) => formatted_run_status,
+        Err(error) => {
+            print_error(&error);
+            return ExitStatus::for_error(&error);
+        }
+    };
 
-    let span = info_span!("printing violations", num_violations = violations.len()).entered();
+    let span = info_span!("printing violations").entered();
 
-    for violation in violations {
-        violation.print(&config);
+    if matches!(exit_status, ExitStatus::SetupError) {
+        eprint!("{stderr}");
+        return exit_status;
+    }
+    if let Some(fixed_file_contents) = fixed_file_contents.as_ref() {
+        io::stdout().write_all(fixed_file_contents).unwrap();
     }
+    match config.output_file.as_ref() {
+        Some(output_file) => {
+            if let Err(source) = fs::write(output_file, output) {
+                print_error(&Error::OutputFileWrite {
+                    path: output_file.clone(),
+                    source,
+                });
+                return ExitStatus::SetupError;
+            }
+        }
+        None => print!("{output}"),
+    }
+    eprint!("{stderr}");
 
     span.exit();
 
-    ExitStatus::FoundViolations
+    if config.write_baseline {
+        eprintln!("Wrote {}", config.baseline_path.as_ref().unwrap().display());
+    }
+
+    if let Err(error) = output_timings(&config) {
+        print_error(&error);
+        return ExitStatus::SetupError;
+    }
+
+    exit_status
+}
+
+// The daemon listens on a Unix domain socket
+#[cfg(not(unix))]
+pub fn run_daemon(
+    _args: Args,
+    _args_to_config: impl Fn(Args) -> Result<Config, Error>,
+    _from_file_run_context_instance_provider_factory: &dyn FromFileRunContextInstanceProviderFactory,
+) -> ExitStatus {
+    print_error(&Error::DaemonUnsupported);
+    ExitStatus::SetupError
+}
+
+pub(crate) struct FormattedRunStatus {
+    pub stdout: String,
+    pub stderr: String,
+    pub exit_status: ExitStatus,
+}
+
+// Everything between linting and outputting that `run_and_output()` and the
+// daemon have in common
+pub(crate) fn format_run_status(
+    config: &Config,
+    run_status: RunStatus,
+    get_file_contents: impl Fn(&Path) -> Option<Vec<u8>>,
+) -> FormattedRunStatus {
+    let setup_error = |error: &Error| FormattedRunStatus {
+        stdout: Default::default(),
+        stderr: format!("{}\n", format_error(error)),
+        exit_status: ExitStatus::SetupError,
+    };
+    let RunStatus {
+        violations,
+        fix_write_failures,
+    } = run_status;
+    let (violations, stale_baseline_entries) = match apply_baseline(config, violations) {
+        Ok(filtered) => filtered,
+        Err(error) => return setup_error(&error),
+    };
+    let exit_status = get_exit_status(&violations, config);
+    let violations = filter_for_quiet(violations, config);
+    let stdout = match format_violations(&violations, config, get_file_contents) {
+        Ok(stdout) => stdout,
+        Err(error) => return setup_error(&error),
+    };
+    let mut stderr = String::new();
+    for warning in config.take_warnings() {
+        stderr.push_str(&format!("Warning: {warning}\n"));
+    }
+    if config.report_stale_baseline_entries {
+        for stale_baseline_entry in stale_baseline_entries {
+            stderr.push_str(&format!("No longer occurs: {stale_baseline_entry}\n"));
+        }
+    }
+    // the violations still get reported for files whose fixes couldn't be
+    // written
+    if !fix_write_failures.is_empty() {
+        stderr.push_str(&format!(
+            "{}\n",
+            format_error(&Error::FixWrite {
+                failures: fix_write_failures,
+            })
+        ));
+        return FormattedRunStatus {
+            stdout,
+            stderr,
+            exit_status: ExitStatus::FixWriteError,
+        };
+    }
+    FormattedRunStatus {
+        stdout,
+        stderr,
+        exit_status,
+    }
+}
+
+fn get_exit_status(violations: &[ViolationWithContext], config: &Config) -> ExitStatus {
+    if violations
+        .iter()
+        .any(|violation| violation.is_internal_error)
+    {
+        return ExitStatus::InternalError;
+    }
+    let num_warnings = violations
+        .iter()
+        .filter(|violation| violation.is_warning())
+        .count();
+    if num_warnings < violations.len()
+        || config
+            .max_warnings
+            .is_some_and(|max_warnings| num_warnings > max_warnings)
+    {
+        ExitStatus::FoundViolations
+    } else {
+        ExitStatus::Ok
+    }
+}
+
+// --quiet only affects what gets output, warnings still count towards
+// --max-warnings
+fn filter_for_quiet(
+    mut violations: Vec<ViolationWithContext>,
+    config: &Config,
+) -> Vec<ViolationWithContext> {
+    if config.quiet {
+        violations.retain(|violation| !violation.is_warning());
+    }
+    violations
+}
+
+fn run_command(command: &Command, config: &Config) -> ExitStatus {
+    match command {
+        Command::Schema => {
+            println!(
+                "{}",
+                serde_json::to_string_pretty(&get_config_schema(config)).unwrap()
+            );
+        }
+        Command::ListRules => {
+            print_rule_list(config);
+        }
+        Command::Explain { rule } => match get_rule_explanation(config, rule) {
+            Ok(explanation) => println!("{explanation}"),
+            Err(error) => {
+                print_error(&error);
+                return ExitStatus::SetupError;
+            }
+        },
+        // the local binary starts the daemon before resolving config
+        Command::Daemon => {
+            print_error(&Error::DaemonFromConfig);
+            return ExitStatus::SetupError;
+        }
+        Command::Docs { plugin, output_dir } => {
+            match generate_rule_docs(config, plugin.as_deref(), output_dir) {
+                Ok(written) => {
+                    for path in written {
+                        println!("Wrote {}", path.display());
+                    }
+                }
+                Err(error) => {
+                    print_error(&error);
+                    return ExitStatus::SetupError;
+                }
+            }
+        }
+    }
+    ExitStatus::Ok
+}
+
+pub fn print_error(error: &Error) {
+    eprintln!("{}", format_error(error));
+}
+
+fn format_error(error: &Error) -> String {
+    use colored::Colorize;
+
+    format!("{} {error}", "error:".red().bold())
+}
+
+fn output_timings(config: &Config) -> Result<(), Error> {
+    if let Some(timing_limit) = config.timing {
+        config.timings.print(config, timing_limit);
+    }
+    if let Some(timing_json_path) = config.timing_json_path.as_ref() {
+        config.timings.write_json(config, timing_json_path)?;
+    }
+    Ok(())
 }
 
 #[instrument(level = "debug", skip_all)]
 pub fn run(
     config: &Config,
     from_file_run_context_instance_provider_factory: &dyn FromFileRunContextInstanceProviderFactory,
-) -> Vec<ViolationWithContext> {
-    let instantiated_rules = config.get_instantiated_rules();
-    let aggregated_queries = AggregatedQueries::new(&instantiated_rules);
-    let tree_sitter_grep_args = get_tree_sitter_grep_args(&aggregated_queries, config, None);
+) -> Result<RunStatus, Error> {
+    run_with_per_config_context(
+        config,
+        from_file_run_context_instance_provider_factory,
+        &get_per_config_context(config)?,
+    )
+}
+
+fn read_stdin() -> Result<Vec<u8>, Error> {
+    let mut file_contents: Vec<u8> = Default::default();
+    io::stdin()
+        .read_to_end(&mut file_contents)
+        .map_err(Error::Stdin)?;
+    Ok(file_contents)
+}
+
+// `FormattedRunStatus::stdout` is the report, which in --stdin --fix mode gets
+// moved to stderr (unless it's going to --output-file) to make way for the
+// fixed source
+pub(crate) struct FormattedStdinRunStatus {
+    pub formatted_run_status: FormattedRunStatus,
+    pub fixed_file_contents: Option<Vec<u8>>,
+}
+
+pub(crate) fn run_and_format_for_stdin(
+    config: &Config,
+    from_file_run_context_instance_provider_factory: &dyn FromFileRunContextInstanceProviderFactory,
+    stdin_filename: &Path,
+    file_contents: Vec<u8>,
+) -> Result<FormattedStdinRunStatus, Error> {
+    let StdinRunStatus {
+        violations,
+        file_contents,
+    } = run_for_stdin(
+        config,
+        from_file_run_context_instance_provider_factory,
+        stdin_filename,
+        file_contents,
+    )?;
+    let mut formatted_run_status = format_run_status(
+        config,
+        RunStatus {
+            violations,
+            fix_write_failures: Default::default(),
+        },
+        |_| Some(file_contents.clone()),
+    );
+    if !config.fix {
+        return Ok(FormattedStdinRunStatus {
+            formatted_run_status,
+            fixed_file_contents: None,
+        });
+    }
+    if config.output_file.is_none() {
+        let report = mem::take(&mut formatted_run_status.stdout);
+        formatted_run_status.stderr.insert_str(0, &report);
+    }
+    Ok(FormattedStdinRunStatus {
+        formatted_run_status,
+        fixed_file_contents: Some(file_contents),
+    })
+}
+
+struct StdinRunStatus {
+    violations: Vec<ViolationWithContext>,
+    // fixed, in --fix mode
+    file_contents: Vec<u8>,
+}
+
+#[instrument(level = "debug", skip_all, fields(?stdin_filename))]
+fn run_for_stdin(
+    config: &Config,
+    from_file_run_context_instance_provider_factory: &dyn FromFileRunContextInstanceProviderFactory,
+    stdin_filename: &Path,
+    mut file_contents: Vec<u8>,
+) -> Result<StdinRunStatus, Error> {
+    let cascaded_config = config.get_cascaded_config_for_path(stdin_filename)?;
+    let top_level_per_config_context;
+    let per_config_context = match cascaded_config.as_ref() {
+        Some(cascaded_config) => &cascaded_config.per_config_context,
+        None => {
+            top_level_per_config_context = get_per_config_context(config)?;
+            &top_level_per_config_context
+        }
+    };
+    // unlinted source gets passed through as-is so that eg editor integrations
+    // can unconditionally replace the buffer contents
+    let Some(supported_language_language) = get_supported_language_language_for_path(
+        stdin_filename,
+    )
+    .filter(|supported_language_language| {
+        per_config_context
+            .borrow_aggregated_queries()
+            .per_language
+            .contains_key(supported_language_language)
+    }) else {
+        return Ok(StdinRunStatus {
+            violations: Default::default(),
+            file_contents,
+        });
+    };
+    if !config.fix {
+        return Ok(StdinRunStatus {
+            violations: run_for_slice(
+                &*file_contents,
+                None,
+                stdin_filename,
+                config,
+                supported_language_language,
+                from_file_run_context_instance_provider_factory,
+                Some(per_config_context),
+            )?
+            .violations,
+            file_contents,
+        });
+    }
+    let FixingForSliceRunStatus { violations, .. } = run_fixing_for_slice_with_per_config_context(
+        &mut file_contents,
+        None,
+        stdin_filename,
+        config,
+        supported_language_language,
+        from_file_run_context_instance_provider_factory,
+        Default::default(),
+        per_config_context,
+    );
+    Ok(StdinRunStatus {
+        violations,
+        file_contents,
+    })
+}
+
+fn run_with_per_config_context(
+    config: &Config,
+    from_file_run_context_instance_provider_factory: &dyn FromFileRunContextInstanceProviderFactory,
+    top_level_per_config_context: &PerConfigContext,
+) -> Result<RunStatus, Error> {
+    let changed_files = ChangedFiles::for_config(config)?;
+    let mut run_status = match changed_files.as_ref() {
+        Some(changed_files) if config.staged => RunStatus {
+            violations: run_for_staged_files(
+                config,
+                from_file_run_context_instance_provider_factory,
+                top_level_per_config_context,
+                changed_files,
+            )?,
+            fix_write_failures: Default::default(),
+        },
+        // otherwise there'd be nothing to stop it from linting the current
+        // directory
+        Some(changed_files) if changed_files.changed_lines.is_empty() => RunStatus {
+            violations: Default::default(),
+            fix_write_failures: Default::default(),
+        },
+        Some(changed_files) => run_for_paths(
+            config,
+            from_file_run_context_instance_provider_factory,
+            top_level_per_config_context,
+            changed_files.paths(),
+        )?,
+        None => run_for_paths(
+            config,
+            from_file_run_context_instance_provider_factory,
+            top_level_per_config_context,
+            config.paths.clone(),
+        )?,
+    };
+    if config.changed_lines_only {
+        if let Some(changed_files) = changed_files.as_ref() {
+            run_status
+                .violations
+                .retain(|violation| changed_files.contains_violation(violation));
+        }
+    }
+    Ok(run_status)
+}
+
+#[instrument(level = "debug", skip_all)]
+fn run_for_staged_files(
+    config: &Config,
+    from_file_run_context_instance_provider_factory: &dyn FromFileRunContextInstanceProviderFactory,
+    top_level_per_config_context: &PerConfigContext,
+    changed_files: &ChangedFiles,
+) -> Result<Vec<ViolationWithContext>, Error> {
+    Ok(changed_files
+        .paths()
+        .into_par_iter()
+        .map(|path| -> Result<Vec<ViolationWithContext>, Error> {
+            let Some(supported_language_language) = get_supported_language_language_for_path(&path)
+            else {
+                return Ok(Default::default());
+            };
+            let cascaded_config = config.get_cascaded_config_for_path(&path)?;
+            let per_config_context = cascaded_config
+                .as_ref()
+                .map_or(top_level_per_config_context, |cascaded_config| {
+                    &cascaded_config.per_config_context
+                });
+            if !per_config_context
+                .borrow_aggregated_queries()
+                .per_language
+                .contains_key(&supported_language_language)
+            {
+                return Ok(Default::default());
+            }
+            let file_contents = get_staged_file_contents(&path)?;
+            Ok(run_for_slice(
+                &*file_contents,
+                None,
+                &path,
+                config,
+                supported_language_language,
+                from_file_run_context_instance_provider_factory,
+                Some(per_config_context),
+            )?
+            .violations)
+        })
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .flatten()
+        .collect())
+}
+
+fn run_for_paths(
+    config: &Config,
+    from_file_run_context_instance_provider_factory: &dyn FromFileRunContextInstanceProviderFactory,
+    top_level_per_config_context: &PerConfigContext,
+    paths: Vec<PathBuf>,
+) -> Result<RunStatus, Error> {
+    // nested config files only get consulted for files in languages that the
+    // top-level config lints
+    let tree_sitter_grep_args = get_tree_sitter_grep_args(
+        top_level_per_config_context.borrow_aggregated_queries(),
+        paths,
+        None,
+    );
+    let setup_error: Mutex<Option<Error>> = Default::default();
     let all_violations: DashMap<PathBuf, Vec<ViolationWithContext>> = Default::default();
     let files_with_fixes: AllPendingFixes = Default::default();
 
@@ -130,10 +669,29 @@ pub fn run(
 
     tree_sitter_grep::run_with_single_per_file_callback(
         tree_sitter_grep_args,
-        |dir_entry, supported_language_language, file_contents, tree, query| {
+        |dir_entry, supported_language_language, file_contents, tree, _| {
+            let path = dir_entry.path();
+            let cascaded_config = match config.get_cascaded_config_for_path(path) {
+                Ok(cascaded_config) => cascaded_config,
+                Err(error) => {
+                    setup_error.lock().unwrap().get_or_insert(error);
+                    return;
+                }
+            };
+            let per_config_context = cascaded_config
+                .as_ref()
+                .map_or(top_level_per_config_context, |cascaded_config| {
+                    &cascaded_config.per_config_context
+                });
+            let aggregated_queries = per_config_context.borrow_aggregated_queries();
+            let Some(aggregated_queries_per_language) = aggregated_queries
+                .per_language
+                .get(&supported_language_language)
+            else {
+                return;
+            };
             let from_file_run_context_instance_provider =
                 from_file_run_context_instance_provider_factory.create();
-            let path = dir_entry.path();
             let node_parent_cache = get_node_parent_cache(tree);
             run_per_file(
                 FileRunContext::new(
@@ -142,9 +700,9 @@ pub fn run(
                     tree,
                     config,
                     supported_language_language,
-                    &aggregated_queries,
-                    query,
-                    &instantiated_rules,
+                    aggregated_queries,
+                    &aggregated_queries_per_language.query,
+                    per_config_context.borrow_instantiated_rules(),
                     None,
                     &*from_file_run_context_instance_provider,
                     if config.fix {
@@ -174,10 +732,14 @@ pub fn run(
             );
         },
     )
-    .unwrap();
+    .map_err(|error| Error::FileSearch(error.to_string()))?;
 
     span.exit();
 
+    if let Some(error) = setup_error.into_inner().unwrap() {
+        return Err(error);
+    }
+
     if !config.fix {
         let violations = all_violations
             .into_iter()
@@ -189,7 +751,10 @@ pub fn run(
             "non-fixing mode, returning after initial pass"
         );
 
-        return violations;
+        return Ok(RunStatus {
+            violations,
+            fix_write_failures: Default::default(),
+        });
     }
     let files_with_fixes = files_with_fixes.into_inner();
     if files_with_fixes.is_empty() {
@@ -203,48 +768,80 @@ pub fn run(
             "fixing mode, returning after initial pass"
         );
 
-        return violations;
+        return Ok(RunStatus {
+            violations,
+            fix_write_failures: Default::default(),
+        });
     }
 
     let span = info_span!("running fixing loop for all files").entered();
 
+    let fix_reviewer = config
+        .fix_interactive
+        .then(|| Mutex::new(FixReviewer::for_terminal()));
+    let run_fixing_loop_for_file = |(
+        path,
+        PerFilePendingFixes {
+            mut file_contents,
+            pending_fixes,
+            language,
+            tree,
+        },
+    ): (PathBuf, PerFilePendingFixes)| {
+        // already successfully resolved during the first pass
+        let cascaded_config = config.get_cascaded_config_for_path(&path).unwrap();
+        let per_config_context = cascaded_config
+            .as_ref()
+            .map_or(top_level_per_config_context, |cascaded_config| {
+                &cascaded_config.per_config_context
+            });
+        // the review needs to know which violation each of the first
+        // pass's fixes came from
+        let mut violations: Vec<ViolationWithContext> = match fix_reviewer.as_ref() {
+            Some(_) => all_violations
+                .get(&path)
+                .map(|violations| violations.value().clone())
+                .unwrap_or_default(),
+            None => Default::default(),
+        };
+        run_fixing_loop(
+            &mut violations,
+            &mut file_contents,
+            pending_fixes,
+            per_config_context.borrow_aggregated_queries(),
+            &path,
+            config,
+            language,
+            per_config_context.borrow_instantiated_rules(),
+            tree,
+            from_file_run_context_instance_provider_factory,
+            RunKind::CommandLineFixingInitial,
+            fix_reviewer.as_ref(),
+        );
+        (path, (file_contents, violations))
+    };
     let aggregated_results_from_files_with_fixes: HashMap<
         PathBuf,
         (Vec<u8>, Vec<ViolationWithContext>),
-    > = files_with_fixes
-        .into_par_iter()
-        .map(
-            |(
-                path,
-                PerFilePendingFixes {
-                    mut file_contents,
-                    pending_fixes,
-                    language,
-                    tree,
-                },
-            )| {
-                let mut violations: Vec<ViolationWithContext> = Default::default();
-                run_fixing_loop(
-                    &mut violations,
-                    &mut file_contents,
-                    pending_fixes,
-                    &aggregated_queries,
-                    &path,
-                    config,
-                    language,
-                    &instantiated_rules,
-                    tree,
-                    from_file_run_context_instance_provider_factory,
-                    RunKind::CommandLineFixingInitial,
-                );
-                (path, (file_contents, violations))
-            },
-        )
-        .collect();
+    > = match fix_reviewer.as_ref() {
+        // prompt file by file rather than interleaving files
+        Some(_) => {
+            let mut files_with_fixes = files_with_fixes.into_iter().collect::<Vec<_>>();
+            files_with_fixes.sort_by(|(a, _), (b, _)| a.cmp(b));
+            files_with_fixes
+                .into_iter()
+                .map(run_fixing_loop_for_file)
+                .collect()
+        }
+        None => files_with_fixes
+            .into_par_iter()
+            .map(run_fixing_loop_for_file)
+            .collect(),
+    };
 
     span.exit();
 
-    write_files(
+    let fix_write_failures = write_files(
         aggregated_results_from_files_with_fixes
             .iter()
             .map(|(path, (file_contents, _))| (&**path, &**file_contents)),
@@ -252,10 +849,86 @@ pub fn run(
     for (path, (_, violations)) in aggregated_results_from_files_with_fixes {
         all_violations.insert(path, violations);
     }
-    all_violations
-        .into_iter()
-        .flat_map(|(_, value)| value)
-        .collect()
+    Ok(RunStatus {
+        violations: all_violations
+            .into_iter()
+            .flat_map(|(_, value)| value)
+            .collect(),
+        fix_write_failures,
+    })
+}
+
+#[instrument(level = "debug", skip_all)]
+pub fn run_with_per_file_callback(
+    config: &Config,
+    from_file_run_context_instance_provider_factory: &dyn FromFileRunContextInstanceProviderFactory,
+    on_file_violations: impl Fn(&Path, Vec<ViolationWithContext>) + Sync,
+) -> Result<(), Error> {
+    if config.fix {
+        return Err(Error::FixingWithPerFileCallback);
+    }
+    let top_level_per_config_context = get_per_config_context(config)?;
+    let tree_sitter_grep_args = get_tree_sitter_grep_args(
+        top_level_per_config_context.borrow_aggregated_queries(),
+        config.paths.clone(),
+        None,
+    );
+    let setup_error: Mutex<Option<Error>> = Default::default();
+
+    tree_sitter_grep::run_with_single_per_file_callback(
+        tree_sitter_grep_args,
+        |dir_entry, supported_language_language, file_contents, tree, _| {
+            let path = dir_entry.path();
+            let cascaded_config = match config.get_cascaded_config_for_path(path) {
+                Ok(cascaded_config) => cascaded_config,
+                Err(error) => {
+                    setup_error.lock().unwrap().get_or_insert(error);
+                    return;
+                }
+            };
+            let per_config_context = cascaded_config
+                .as_ref()
+                .map_or(&top_level_per_config_context, |cascaded_config| {
+                    &cascaded_config.per_config_context
+                });
+            let aggregated_queries = per_config_context.borrow_aggregated_queries();
+            let Some(aggregated_queries_per_language) = aggregated_queries
+                .per_language
+                .get(&supported_language_language)
+            else {
+                return;
+            };
+            let from_file_run_context_instance_provider =
+                from_file_run_context_instance_provider_factory.create();
+            let node_parent_cache = get_node_parent_cache(tree);
+            let mut file_violations: Vec<ViolationWithContext> = Default::default();
+            run_per_file(
+                FileRunContext::new(
+                    path,
+                    file_contents,
+                    tree,
+                    config,
+                    supported_language_language,
+                    aggregated_queries,
+                    &aggregated_queries_per_language.query,
+                    per_config_context.borrow_instantiated_rules(),
+                    None,
+                    &*from_file_run_context_instance_provider,
+                    RunKind::CommandLineNonfixing,
+                    &config.environment,
+                    &node_parent_cache,
+                ),
+                |violations| file_violations.extend(violations),
+                |_, _| unreachable!(),
+            );
+            on_file_violations(path, file_violations);
+        },
+    )
+    .map_err(|error| Error::FileSearch(error.to_string()))?;
+    match setup_error.into_inner().unwrap() {
+        Some(error) => Err(error),
+        None => Ok(()),
+    }
 }
 
 #[instrument(skip_all, fields(path = ?file_run_context.path, language = ?file_run_context.language()))]
@@ -264,8 +937,7 @@ fn run_per_file<'a, 'b>(
     mut on_found_violations: impl FnMut(Vec<ViolationWithContext>),
     mut on_found_pending_fixes: impl FnMut(Vec<PendingFix>, &InstantiatedRule),
 ) {
-    let mut instantiated_per_file_rules: HashMap<RuleName, Box<dyn RuleInstancePerFile<'a> + 'a>> =
-        Default::default();
+    let mut instantiated_per_file_rules: InstantiatedPerFileRules<'a> = Default::default();
     let mut node_stack: Vec<Node<'a>> = Vec::with_capacity(16);
     let mut saw_match = false;
     let wildcard_listener_pattern_index = file_run_context
@@ -325,7 +997,7 @@ fn run_per_file<'a, 'b>(
 fn run_match<'a, 'b, 'c>(
     file_run_context: FileRunContext<'a, 'b>,
     query_match: &'c QueryMatch<'a, 'a>,
-    instantiated_per_file_rules: &mut HashMap<RuleName, Box<dyn RuleInstancePerFile<'a> + 'a>>,
+    instantiated_per_file_rules: &mut InstantiatedPerFileRules<'a>,
     node_stack: &mut Vec<Node<'a>>,
     wildcard_listener_pattern_index: usize,
     mut on_found_violations: impl FnMut(Vec<ViolationWithContext>),
@@ -431,7 +1103,7 @@ fn run_match<'a, 'b, 'c>(
 fn run_exit_node_listeners<'a, 'b>(
     exited_node: Node<'a>,
     file_run_context: FileRunContext<'a, 'b>,
-    instantiated_per_file_rules: &mut HashMap<RuleName, Box<dyn RuleInstancePerFile<'a> + 'a>>,
+    instantiated_per_file_rules: &mut InstantiatedPerFileRules<'a>,
     mut on_found_violations: impl FnMut(Vec<ViolationWithContext>),
     mut on_found_pending_fixes: impl FnMut(Vec<PendingFix>, &InstantiatedRule),
 ) {
@@ -460,7 +1132,7 @@ fn run_exit_node_listeners<'a, 'b>(
 fn run_enter_node_listeners<'a, 'b>(
     entered_node: Node<'a>,
     file_run_context: FileRunContext<'a, 'b>,
-    instantiated_per_file_rules: &mut HashMap<RuleName, Box<dyn RuleInstancePerFile<'a> + 'a>>,
+    instantiated_per_file_rules: &mut InstantiatedPerFileRules<'a>,
     mut on_found_violations: impl FnMut(Vec<ViolationWithContext>),
     mut on_found_pending_fixes: impl FnMut(Vec<PendingFix>, &InstantiatedRule),
 ) {
@@ -489,7 +1161,7 @@ fn run_enter_node_listeners<'a, 'b>(
 fn run_single_on_query_match_callback<'a, 'b, 'c>(
     file_run_context: FileRunContext<'a, 'b>,
     instantiated_rule: &'a InstantiatedRule,
-    instantiated_per_file_rules: &mut HashMap<RuleName, Box<dyn RuleInstancePerFile<'a> + 'a>>,
+    instantiated_per_file_rules: &mut InstantiatedPerFileRules<'a>,
     rule_listener_index: usize,
     node_or_captures: NodeOrCaptures<'a, 'c>,
     on_found_violations: impl FnOnce(Vec<ViolationWithContext>),
@@ -498,34 +1170,84 @@ fn run_single_on_query_match_callback<'a, 'b, 'c>(
     trace!("running single on query match callback");
 
     let query_match_context = QueryMatchContext::new(file_run_context, instantiated_rule);
-    instantiated_per_file_rules
-        .entry(instantiated_rule.meta.name.clone())
-        .or_insert_with(|| {
-            let _span = debug_span!(
-                "instantiate rule per file",
-                name = instantiated_rule.meta.name
-            )
-            .entered();
+    let node = match &node_or_captures {
+        NodeOrCaptures::Node(node) => *node,
+        NodeOrCaptures::Captures(captures) => captures.query_match.captures[0].node,
+    };
+    if !instantiated_per_file_rules.contains_key(&instantiated_rule.meta.name) {
+        let _span = debug_span!(
+            "instantiate rule per file",
+            name = instantiated_rule.meta.name
+        )
+        .entered();
 
-            instantiated_rule
-                .rule_instance
-                .clone()
-                .instantiate_per_file(file_run_context)
-        })
-        .thrush(|rule_instance_per_file| {
-            let _span = debug_span!(
-                "run rule listener callback",
-                name = instantiated_rule.meta.name,
-                rule_listener_index
-            )
-            .entered();
+        let rule_instance_per_file = panic::catch_unwind(AssertUnwindSafe(|| {
+            Timings::time(file_run_context.config, instantiated_rule, None, || {
+                instantiated_rule
+                    .rule_instance
+                    .clone()
+                    .instantiate_per_file(file_run_context)
+            })
+        }));
+        match rule_instance_per_file {
+            Ok(rule_instance_per_file) => {
+                instantiated_per_file_rules.insert(
+                    instantiated_rule.meta.name.clone(),
+                    Some(rule_instance_per_file),
+                );
+            }
+            Err(panic_payload) => {
+                instantiated_per_file_rules.insert(instantiated_rule.meta.name.clone(), None);
+                on_found_violations(vec![ViolationWithContext::internal_error(
+                    file_run_context,
+                    instantiated_rule,
+                    node,
+                    &*panic_payload,
+                )]);
+                return;
+            }
+        }
+    }
+    let rule_instance_per_file_slot = instantiated_per_file_rules
+        .get_mut(&instantiated_rule.meta.name)
+        .unwrap();
+    let Some(rule_instance_per_file) = rule_instance_per_file_slot.as_mut() else {
+        // rule has been disabled for this file after panicking
+        return;
+    };
+    {
+        let _span = debug_span!(
+            "run rule listener callback",
+            name = instantiated_rule.meta.name,
+            rule_listener_index
+        )
+        .entered();
 
-            rule_instance_per_file.on_query_match(
-                rule_listener_index,
-                node_or_captures,
-                &query_match_context,
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            Timings::time(
+                file_run_context.config,
+                instantiated_rule,
+                Some(rule_listener_index),
+                || {
+                    rule_instance_per_file.on_query_match(
+                        rule_listener_index,
+                        node_or_captures,
+                        &query_match_context,
+                    );
+                },
             );
-        });
+        }));
+        if let Err(panic_payload) = result {
+            *rule_instance_per_file_slot = None;
+            on_found_violations(vec![ViolationWithContext::internal_error(
+                file_run_context,
+                instantiated_rule,
+                node,
+                &*panic_payload,
+            )]);
+            return;
+        }
+    }
 
     if let Some(violations) = query_match_context.violations.take() {
         on_found_violations(violations);
@@ -551,6 +1273,19 @@ impl fmt::Debug for PerConfigContext {
     }
 }
 
+fn get_per_config_context(config: &Config) -> Result<PerConfigContext, Error> {
+    PerConfigContextTryBuilder {
+        instantiated_rules: config.get_instantiated_rules()?,
+        aggregated_queries_builder: |instantiated_rules| AggregatedQueries::new(instantiated_rules),
+    }
+    .try_build()
+}
+
+pub struct RunStatus {
+    pub violations: Vec<ViolationWithContext>,
+    pub fix_write_failures: Vec<FixWriteFailure>,
+}
+
 pub struct SliceRunStatus {
     pub violations: Vec<ViolationWithContext>,
     // pub from_file_run_context_instance_provider: Box<dyn FromFileRunContextInstanceProvider>,
@@ -566,26 +1301,16 @@ pub fn run_for_slice<'a>(
     supported_language_language: SupportedLanguageLanguage,
     from_file_run_context_instance_provider_factory: &dyn FromFileRunContextInstanceProviderFactory,
     per_config_context: Option<&PerConfigContext>,
-) -> SliceRunStatus {
+) -> Result<SliceRunStatus, Error> {
     let file_contents = file_contents.into();
     let path = path.as_ref();
     if config.fix {
         panic!("Use run_fixing_for_slice()");
     }
-    let per_config_context: MaybeOwned<'_, PerConfigContext> = per_config_context.map_or_else(
-        || {
-            MaybeOwned::Owned(
-                PerConfigContextBuilder {
-                    instantiated_rules: config.get_instantiated_rules(),
-                    aggregated_queries_builder: |instantiated_rules| {
-                        AggregatedQueries::new(instantiated_rules)
-                    },
-                }
-                .build(),
-            )
-        },
-        MaybeOwned::Borrowed,
-    );
+    let per_config_context: MaybeOwned<'_, PerConfigContext> = match per_config_context {
+        Some(per_config_context) => MaybeOwned::Borrowed(per_config_context),
+        None => MaybeOwned::Owned(get_per_config_context(config)?),
+    };
     let violations: Mutex<Vec<ViolationWithContext>> = Default::default();
     let tree = tree.unwrap_or_else(|| {
         let _span = debug_span!("tree-sitter parse").entered();
@@ -631,16 +1356,15 @@ pub fn run_for_slice<'a>(
         },
     );
     drop(from_file_run_context_instance_provider);
-    SliceRunStatus {
+    Ok(SliceRunStatus {
         violations: violations.into_inner().unwrap(),
         per_config_context: match per_config_context {
             MaybeOwned::Borrowed(_) => None,
             MaybeOwned::Owned(per_config_context) => Some(per_config_context),
         }, // from_file_run_context_instance_provider,
-    }
+    })
 }
 
-#[instrument(skip_all, fields(path = ?path.as_ref(), ?supported_language_language))]
 pub fn run_fixing_for_slice<'a>(
     file_contents: impl Into<MutRopeOrSlice<'a>>,
     tree: Option<Tree>,
@@ -649,14 +1373,39 @@ pub fn run_fixing_for_slice<'a>(
     supported_language_language: SupportedLanguageLanguage,
     from_file_run_context_instance_provider_factory: &dyn FromFileRunContextInstanceProviderFactory,
     context: FixingForSliceRunContext,
-) -> FixingForSliceRunStatus {
-    let file_contents = file_contents.into();
-    let path = path.as_ref();
+) -> Result<FixingForSliceRunStatus, Error> {
     if !config.fix {
         panic!("Use run_for_slice()");
     }
-    let instantiated_rules = config.get_instantiated_rules();
-    let aggregated_queries = AggregatedQueries::new(&instantiated_rules);
+    Ok(run_fixing_for_slice_with_per_config_context(
+        file_contents,
+        tree,
+        path,
+        &config,
+        supported_language_language,
+        from_file_run_context_instance_provider_factory,
+        context,
+        &get_per_config_context(&config)?,
+    ))
+}
+
+// Lets eg --stdin reuse an already-resolved (cascaded) config
+#[allow(clippy::too_many_arguments)]
+#[instrument(skip_all, fields(path = ?path.as_ref(), ?supported_language_language))]
+fn run_fixing_for_slice_with_per_config_context<'a>(
+    file_contents: impl Into<MutRopeOrSlice<'a>>,
+    tree: Option<Tree>,
+    path: impl AsRef<Path>,
+    config: &Config,
+    supported_language_language: SupportedLanguageLanguage,
+    from_file_run_context_instance_provider_factory: &dyn FromFileRunContextInstanceProviderFactory,
+    context: FixingForSliceRunContext,
+    per_config_context: &PerConfigContext,
+) -> FixingForSliceRunStatus {
+    let file_contents = file_contents.into();
+    let path = path.as_ref();
+    let aggregated_queries = per_config_context.borrow_aggregated_queries();
+    let instantiated_rules = per_config_context.borrow_instantiated_rules();
     let tree = tree.unwrap_or_else(|| {
         let _span = debug_span!("tree-sitter parse").entered();
 
@@ -679,15 +1428,15 @@ pub fn run_fixing_for_slice<'a>(
             path,
             &file_contents,
             &tree,
-            &config,
+            config,
             supported_language_language,
-            &aggregated_queries,
+            aggregated_queries,
             &aggregated_queries
                 .per_language
                 .get(&supported_language_language)
                 .unwrap()
                 .query,
-            &instantiated_rules,
+            instantiated_rules,
             // TODO: here could wire up "remembered" changed
             // ranges for LSP server use case?
             None,
@@ -715,7 +1464,6 @@ pub fn run_fixing_for_slice<'a>(
         drop(from_file_run_context_instance_provider);
         return FixingForSliceRunStatus {
             violations,
-            instantiated_rules,
             edits: Default::default(),
         };
     }
@@ -724,18 +1472,18 @@ pub fn run_fixing_for_slice<'a>(
         &mut violations,
         file_contents,
         pending_fixes,
-        &aggregated_queries,
+        aggregated_queries,
         path,
-        &config,
+        config,
         supported_language_language,
-        &instantiated_rules,
+        instantiated_rules,
         tree,
         from_file_run_context_instance_provider_factory,
         RunKind::FixingForSliceInitial { context: &context },
+        None,
     );
     FixingForSliceRunStatus {
         violations,
-        instantiated_rules,
         edits: Some(accumulated_edits),
     }
 }
@@ -743,8 +1491,6 @@ pub fn run_fixing_for_slice<'a>(
 pub struct FixingForSliceRunStatus {
     violations: Vec<ViolationWithContext>,
     #[allow(dead_code)]
-    instantiated_rules: Vec<InstantiatedRule>,
-    #[allow(dead_code)]
     edits: Option<AccumulatedEdits>,
 }
 
@@ -754,9 +1500,31 @@ pub struct FixingForSliceRunContext {
     pub edits_since_last_fixing_run: Option<AccumulatedEdits>,
 }
 
+// Goes by the same (ripgrep) file type definitions that tree-sitter-grep uses
+// to pick which files to parse as which language
+fn get_supported_language_language_for_path(path: &Path) -> Option<SupportedLanguageLanguage> {
+    static TYPES: OnceLock<Types> = OnceLock::new();
+    let types = TYPES.get_or_init(|| {
+        let mut types_builder = TypesBuilder::new();
+        types_builder.add_defaults();
+        for supported_language in SupportedLanguage::value_variants() {
+            types_builder.select(supported_language.name_for_types_builder());
+        }
+        types_builder.build().unwrap()
+    });
+    let Match::Whitelist(glob) = types.matched(path, false) else {
+        return None;
+    };
+    let file_type_name = glob.file_type_def()?.name();
+    SupportedLanguage::value_variants()
+        .iter()
+        .find(|supported_language| supported_language.name_for_types_builder() == file_type_name)
+        .map(|supported_language| supported_language.supported_language_language(Some(path)))
+}
+
 fn get_tree_sitter_grep_args(
     aggregated_queries: &AggregatedQueries,
-    config: &Config,
+    paths: Vec<PathBuf>,
     language: Option<SupportedLanguage>,
 ) -> tree_sitter_grep::Args {
     tree_sitter_grep::ArgsBuilder::default()
@@ -767,19 +1535,13 @@ fn get_tree_sitter_grep_args(
                 .map(|(&language, aggregated_query)| (language, aggregated_query.query.clone()))
                 .collect::<HashMap<_, _>>(),
         )
-        .paths(config.paths.clone())
+        .paths(paths)
         .maybe_language(language)
         .build()
         .unwrap()
 }
 
-#[instrument(level = "debug", skip_all)]
-fn write_files<'a>(files_to_write: impl Iterator<Item = (&'a Path, &'a [u8])>) {
-    for (path, file_contents) in files_to_write {
-        let _span = debug_span!("write file", ?path).entered();
-
-        fs::write(path, file_contents).unwrap();
-    }
-}
-
 type RuleName = String;
+
+type InstantiatedPerFileRules<'a> =
+    HashMap<RuleName, Option<Box<dyn RuleInstancePerFile<'a> + 'a>>>;
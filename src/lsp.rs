@@ -1,37 +1,59 @@
 use std::{
-    collections::HashMap,
-    fmt, ops,
+    collections::{hash_map::DefaultHasher, HashMap, HashSet},
+    fmt, fs,
+    hash::{Hash, Hasher},
+    ops,
     path::{Path, PathBuf},
-    sync::{mpsc::Sender, OnceLock},
-    time::UNIX_EPOCH,
+    sync::{mpsc::Sender, Arc, OnceLock},
 };
 
+use serde::Deserialize;
 use squalid::EverythingExt;
-use tokio::sync::Mutex;
+use tokio::sync::{
+    mpsc::{self, UnboundedReceiver},
+    Mutex,
+};
 use tower_lsp::{
     jsonrpc::Result,
     lsp_types::{
-        Diagnostic, DiagnosticSeverity, DidChangeTextDocumentParams, DidOpenTextDocumentParams,
-        DocumentChanges, ExecuteCommandOptions, ExecuteCommandParams, InitializeParams,
-        InitializeResult, InitializedParams, NumberOrString, OneOf,
-        OptionalVersionedTextDocumentIdentifier, Position, Range, ServerCapabilities,
-        TextDocumentEdit, TextDocumentSyncCapability, TextDocumentSyncKind, TextEdit, Url,
-        WorkspaceEdit,
+        CodeAction, CodeActionKind, CodeActionOrCommand, CodeActionParams,
+        CodeActionProviderCapability, CodeActionResponse, CodeDescription, Diagnostic,
+        DiagnosticOptions, DiagnosticRelatedInformation, DiagnosticServerCapabilities,
+        DiagnosticSeverity, DiagnosticTag, DidChangeTextDocumentParams,
+        DidChangeWatchedFilesParams, DidChangeWorkspaceFoldersParams, DidOpenTextDocumentParams,
+        DidSaveTextDocumentParams, DocumentChanges, DocumentDiagnosticParams,
+        DocumentDiagnosticReport, DocumentDiagnosticReportResult, ExecuteCommandOptions,
+        ExecuteCommandParams, FullDocumentDiagnosticReport, Hover, HoverContents, HoverParams,
+        HoverProviderCapability, InitializeParams, InitializeResult, InitializedParams, Location,
+        MarkupContent, MarkupKind, MessageType, NumberOrString, OneOf,
+        OptionalVersionedTextDocumentIdentifier, Position, Range,
+        RelatedFullDocumentDiagnosticReport, ServerCapabilities, TextDocumentEdit,
+        TextDocumentSyncCapability, TextDocumentSyncKind, TextDocumentSyncOptions,
+        TextDocumentSyncSaveOptions, TextEdit, UnchangedDocumentDiagnosticReport, Url,
+        WorkspaceDiagnosticParams, WorkspaceDiagnosticReport, WorkspaceDiagnosticReportResult,
+        WorkspaceDocumentDiagnosticReport, WorkspaceEdit, WorkspaceFoldersServerCapabilities,
+        WorkspaceFullDocumentDiagnosticReport, WorkspaceServerCapabilities,
+        WorkspaceUnchangedDocumentDiagnosticReport,
     },
     Client, LanguageServer, LspService, Server,
 };
 use tree_sitter_grep::{ropey::Rope, RopeOrSlice, SupportedLanguageLanguage};
 
 use crate::{
+    config::find_config_file_from,
+    explain::push_examples_markdown,
     fixing::{get_newline_offsets_rope_or_slice, AccumulatedEdits},
+    get_supported_language_language_for_path, get_trace_file_path,
     tree_sitter::{self, InputEdit, Parser, Point, Tree},
     tree_sitter_grep::{Parseable, SupportedLanguage},
-    Args, ArgsBuilder, Config, FixingForSliceRunContext, FixingForSliceRunStatus, MutRopeOrSlice,
-    PerConfigContext, SliceRunStatus, ViolationWithContext,
+    Args, Config, Error, ErrorLevel, FixingForSliceRunContext, FixingForSliceRunStatus,
+    MutRopeOrSlice, PerConfigContext, RuleTag, SliceRunStatus, ViolationWithContext,
 };
 
 const APPLY_ALL_FIXES_COMMAND: &str = "tree-sitter-lint.applyAllFixes";
+const DIAGNOSTIC_SOURCE: &str = "tree-sitter-lint";
 
+#[allow(clippy::large_enum_variant)]
 pub enum ArgsOrConfig<'a> {
     Args(Args),
     Config(&'a Config),
@@ -58,7 +80,7 @@ pub trait LocalLinter: Send + Sync {
         args_or_config: ArgsOrConfig,
         language: SupportedLanguage,
         per_config_context: Option<&PerConfigContext>,
-    ) -> (SliceRunStatus, Option<Config>);
+    ) -> std::result::Result<(SliceRunStatus, Option<Config>), Error>;
 
     fn run_fixing_for_slice<'a>(
         &self,
@@ -68,16 +90,43 @@ pub trait LocalLinter: Send + Sync {
         args: Args,
         language: SupportedLanguage,
         context: FixingForSliceRunContext,
-    ) -> FixingForSliceRunStatus;
+    ) -> std::result::Result<FixingForSliceRunStatus, Error>;
+
+    fn run_with_per_file_callback(
+        &self,
+        args: Args,
+        on_file_violations: impl Fn(&Path, Vec<ViolationWithContext>) + Sync,
+    ) -> std::result::Result<(), Error>;
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct InitializationOptions {
+    #[serde(default)]
+    workspace_diagnostics: bool,
+    trace_directory: Option<PathBuf>,
+}
+
+struct PerConfigState {
+    config: Config,
+    per_config_context: PerConfigContext,
 }
 
 struct Backend<TLocalLinter> {
     client: Client,
-    local_linter: TLocalLinter,
-    per_file: Mutex<HashMap<Url, PerFileState>>,
+    local_linter: Arc<TLocalLinter>,
+    per_file: Arc<Mutex<HashMap<Url, PerFileState>>>,
     start_new_trace_sender: Option<Sender<PathBuf>>,
-    per_config_context: OnceLock<PerConfigContext>,
-    config: OnceLock<Config>,
+    per_config: Mutex<HashMap<Option<PathBuf>, Arc<PerConfigState>>>,
+    workspace_folders: Mutex<Vec<PathBuf>>,
+    initialization_options: OnceLock<InitializationOptions>,
+    uses_pull_diagnostics: OnceLock<bool>,
+    // eg a broken config file would otherwise pop up the same message for
+    // every file
+    shown_errors: Arc<Mutex<HashSet<String>>>,
+    // (result id, diagnostics) of the last workspace diagnostic pull's scan,
+    // None until the first pull and whenever something might have changed
+    workspace_diagnostics: Mutex<Option<HashMap<Url, (String, Vec<Diagnostic>)>>>,
 }
 
 impl<TLocalLinter: fmt::Debug> fmt::Debug for Backend<TLocalLinter> {
@@ -87,13 +136,17 @@ impl<TLocalLinter: fmt::Debug> fmt::Debug for Backend<TLocalLinter> {
             .field("local_linter", &self.local_linter)
             .field("per_file", &self.per_file)
             .field("start_new_trace_sender", &self.start_new_trace_sender)
-            .field("per_config_context", &self.per_config_context)
-            // .field("config", &self.config)
+            // .field("per_config", &self.per_config)
+            .field("workspace_folders", &self.workspace_folders)
+            .field("initialization_options", &self.initialization_options)
+            .field("uses_pull_diagnostics", &self.uses_pull_diagnostics)
+            .field("shown_errors", &self.shown_errors)
+            .field("workspace_diagnostics", &self.workspace_diagnostics)
             .finish()
     }
 }
 
-impl<TLocalLinter: LocalLinter> Backend<TLocalLinter> {
+impl<TLocalLinter: LocalLinter + 'static> Backend<TLocalLinter> {
     pub fn new(
         client: Client,
         local_linter: TLocalLinter,
@@ -101,66 +154,186 @@ impl<TLocalLinter: LocalLinter> Backend<TLocalLinter> {
     ) -> Self {
         Self {
             client,
-            local_linter,
+            local_linter: Arc::new(local_linter),
             per_file: Default::default(),
             start_new_trace_sender,
-            per_config_context: Default::default(),
-            config: Default::default(),
+            per_config: Default::default(),
+            workspace_folders: Default::default(),
+            initialization_options: Default::default(),
+            uses_pull_diagnostics: Default::default(),
+            shown_errors: Default::default(),
+            workspace_diagnostics: Default::default(),
         }
     }
 
+    fn are_workspace_diagnostics_enabled(&self) -> bool {
+        self.initialization_options
+            .get()
+            .is_some_and(|initialization_options| initialization_options.workspace_diagnostics)
+    }
+
+    fn uses_pull_diagnostics(&self) -> bool {
+        self.uses_pull_diagnostics
+            .get()
+            .copied()
+            .unwrap_or_default()
+    }
+
     async fn run_linting_and_report_diagnostics(&self, uri: &Url) {
-        let (file_contents, tree, supported_language_language) = {
-            let per_file = self.per_file.lock().await;
-            let per_file_state = per_file.get(uri).unwrap();
+        if self.uses_pull_diagnostics() {
+            return;
+        }
+        let diagnostics = self.run_linting(uri).await;
+        self.client
+            .publish_diagnostics(uri.clone(), diagnostics, None)
+            .await;
+    }
+
+    async fn run_linting(&self, uri: &Url) -> Vec<Diagnostic> {
+        let open_file = self.per_file.lock().await.get(uri).map(|per_file_state| {
             (
                 per_file_state.contents.clone(),
                 per_file_state.tree.clone(),
                 per_file_state.supported_language_language,
             )
+        });
+        let (file_contents, tree, supported_language_language) = match open_file {
+            Some(open_file) => open_file,
+            None => {
+                let Some(file_contents) = uri
+                    .to_file_path()
+                    .ok()
+                    .and_then(|path| fs::read_to_string(path).ok())
+                else {
+                    return Default::default();
+                };
+                let file_contents: Rope = (&*file_contents).into();
+                let supported_language_language = get_supported_language_language(uri);
+                let tree = parse_from_scratch(&file_contents, supported_language_language);
+                (file_contents, tree, supported_language_language)
+            }
         };
-        let per_config_context = self.per_config_context.get();
-        let config = self.config.get();
+        let config_file_path = get_config_file_path(uri);
+        let per_config_state = self.per_config.lock().await.get(&config_file_path).cloned();
         self.start_new_trace("run-for-slice");
-        let (
-            SliceRunStatus {
-                violations,
-                per_config_context: per_config_context_returned,
-            },
-            config_returned,
-        ) = self.local_linter.run_for_slice(
+        let run_status = self.local_linter.run_for_slice(
             &file_contents,
             Some(tree),
             uri.as_str(),
-            config.map_or_else(|| Args::default().into(), Into::into),
+            per_config_state.as_ref().map_or_else(
+                || {
+                    Args {
+                        config: config_file_path.clone(),
+                        ..Default::default()
+                    }
+                    .into()
+                },
+                |per_config_state| (&per_config_state.config).into(),
+            ),
             supported_language_language.supported_language(),
-            per_config_context,
+            per_config_state
+                .as_ref()
+                .map(|per_config_state| &per_config_state.per_config_context),
         );
         self.start_new_trace("everything-else");
-        let should_initially_populate_per_config_context = per_config_context.is_none();
-        if should_initially_populate_per_config_context {
-            let _ = self
-                .per_config_context
-                .set(per_config_context_returned.unwrap());
-            let _ = self.config.set(config_returned.unwrap());
+        let (
+            SliceRunStatus {
+                violations,
+                per_config_context: per_config_context_returned,
+            },
+            config_returned,
+        ) = match run_status {
+            Ok(run_status) => run_status,
+            Err(error) => {
+                show_error(&self.client, &self.shown_errors, &error).await;
+                return Default::default();
+            }
+        };
+        if let Some(config) = per_config_state
+            .as_ref()
+            .map(|per_config_state| &per_config_state.config)
+            .or(config_returned.as_ref())
+        {
+            for warning in config.take_warnings() {
+                self.client
+                    .show_message(
+                        MessageType::WARNING,
+                        format!("{DIAGNOSTIC_SOURCE}: {warning}"),
+                    )
+                    .await;
+            }
         }
-        self.client
-            .publish_diagnostics(
-                uri.clone(),
-                violations
-                    .into_iter()
-                    .map(|violation| Diagnostic {
-                        message: violation.message().into_owned(),
-                        range: tree_sitter_range_to_lsp_range(&file_contents, violation.range),
-                        severity: Some(DiagnosticSeverity::ERROR),
-                        code: Some(NumberOrString::String(violation.rule.name.clone())),
-                        source: Some("tree-sitter-lint".to_owned()),
-                        ..Default::default()
+        let should_initially_populate_per_config_state = per_config_state.is_none();
+        if should_initially_populate_per_config_state {
+            self.per_config
+                .lock()
+                .await
+                .entry(config_file_path)
+                .or_insert_with(|| {
+                    Arc::new(PerConfigState {
+                        config: config_returned.unwrap(),
+                        per_config_context: per_config_context_returned.unwrap(),
                     })
-                    .collect(),
-                None,
-            )
-            .await;
+                });
+        }
+        let diagnostics = violations
+            .iter()
+            .map(|violation| violation_to_diagnostic(violation, &file_contents, uri))
+            .collect();
+        if let Some(per_file_state) = self.per_file.lock().await.get_mut(uri) {
+            per_file_state.last_violations = violations;
+        }
+        diagnostics
+    }
+
+    fn spawn_workspace_folder_scan(&self, workspace_folder: PathBuf) {
+        let client = self.client.clone();
+        let per_file = self.per_file.clone();
+        let shown_errors = self.shown_errors.clone();
+        let mut receiver = lint_workspace_folder(self.local_linter.clone(), workspace_folder);
+        tokio::spawn(async move {
+            while let Some(file_violations) = receiver.recv().await {
+                let (path, violations) = match file_violations {
+                    Ok(file_violations) => file_violations,
+                    Err(error) => {
+                        show_error(&client, &shown_errors, &error).await;
+                        continue;
+                    }
+                };
+                if let Some((uri, diagnostics)) =
+                    get_closed_file_diagnostics(&per_file, &path, &violations).await
+                {
+                    client.publish_diagnostics(uri, diagnostics, None).await;
+                }
+            }
+        });
+    }
+
+    async fn lint_workspace_folders(&self) -> HashMap<Url, (String, Vec<Diagnostic>)> {
+        let mut workspace_diagnostics: HashMap<Url, (String, Vec<Diagnostic>)> = Default::default();
+        let workspace_folders = self.workspace_folders.lock().await.clone();
+        for workspace_folder in workspace_folders {
+            let mut receiver = lint_workspace_folder(self.local_linter.clone(), workspace_folder);
+            while let Some(file_violations) = receiver.recv().await {
+                let (path, violations) = match file_violations {
+                    Ok(file_violations) => file_violations,
+                    Err(error) => {
+                        show_error(&self.client, &self.shown_errors, &error).await;
+                        continue;
+                    }
+                };
+                if let Some((uri, diagnostics)) =
+                    get_closed_file_diagnostics(&self.per_file, &path, &violations).await
+                {
+                    workspace_diagnostics.insert(uri, (get_result_id(&diagnostics), diagnostics));
+                }
+            }
+        }
+        workspace_diagnostics
+    }
+
+    async fn invalidate_workspace_diagnostics(&self) {
+        *self.workspace_diagnostics.lock().await = None;
     }
 
     async fn run_fixing_and_report_fixes(&self, uri: &Url) {
@@ -187,19 +360,30 @@ impl<TLocalLinter: LocalLinter> Backend<TLocalLinter> {
             )
         };
         let mut cloned_contents = file_contents.clone();
-        let FixingForSliceRunStatus {
-            edits, violations, ..
-        } = self.local_linter.run_fixing_for_slice(
+        let run_status = self.local_linter.run_fixing_for_slice(
             &mut cloned_contents,
             Some(tree),
             uri.as_str(),
-            ArgsBuilder::default().fix(true).build().unwrap(),
+            Args {
+                fix: true,
+                config: get_config_file_path(uri),
+                ..Default::default()
+            },
             supported_language_language.supported_language(),
             FixingForSliceRunContext {
                 last_fixing_run_violations,
                 edits_since_last_fixing_run,
             },
         );
+        let FixingForSliceRunStatus {
+            edits, violations, ..
+        } = match run_status {
+            Ok(run_status) => run_status,
+            Err(error) => {
+                show_error(&self.client, &self.shown_errors, &error).await;
+                return;
+            }
+        };
         self.per_file
             .lock()
             .await
@@ -228,32 +412,85 @@ impl<TLocalLinter: LocalLinter> Backend<TLocalLinter> {
     }
 
     fn start_new_trace(&self, trace_name: &str) {
+        let Some(trace_directory) = self
+            .initialization_options
+            .get()
+            .and_then(|initialization_options| initialization_options.trace_directory.as_ref())
+        else {
+            return;
+        };
         if let Some(start_new_trace_sender) = self.start_new_trace_sender.as_ref() {
-            start_new_trace_sender
-                .send(
-                    format!(
-                        "/Users/jrosse/prj/hello-world/trace-{trace_name}-{}.json",
-                        UNIX_EPOCH.elapsed().unwrap().as_micros()
-                    )
-                    .into(),
-                )
-                .unwrap();
+            let _ = start_new_trace_sender.send(get_trace_file_path(trace_directory, trace_name));
         }
     }
 }
 
 #[tower_lsp::async_trait]
 impl<TLocalLinter: LocalLinter + 'static> LanguageServer for Backend<TLocalLinter> {
-    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
+        let initialization_options: InitializationOptions = params
+            .initialization_options
+            .and_then(|initialization_options| serde_json::from_value(initialization_options).ok())
+            .unwrap_or_default();
+        let workspace_diagnostics = initialization_options.workspace_diagnostics;
+        let _ = self.initialization_options.set(initialization_options);
+        let _ = self.uses_pull_diagnostics.set(
+            params
+                .capabilities
+                .text_document
+                .as_ref()
+                .is_some_and(|text_document| text_document.diagnostic.is_some()),
+        );
+        #[allow(deprecated)]
+        let workspace_folders = params
+            .workspace_folders
+            .map(|workspace_folders| {
+                workspace_folders
+                    .into_iter()
+                    .filter_map(|workspace_folder| workspace_folder.uri.to_file_path().ok())
+                    .collect()
+            })
+            .or_else(|| {
+                params
+                    .root_uri
+                    .and_then(|root_uri| root_uri.to_file_path().ok())
+                    .map(|root_path| vec![root_path])
+            })
+            .unwrap_or_default();
+        *self.workspace_folders.lock().await = workspace_folders;
+
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
-                text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                    TextDocumentSyncKind::INCREMENTAL,
+                text_document_sync: Some(TextDocumentSyncCapability::Options(
+                    TextDocumentSyncOptions {
+                        open_close: Some(true),
+                        change: Some(TextDocumentSyncKind::INCREMENTAL),
+                        // for knowing when to rescan for workspace diagnostics
+                        save: Some(TextDocumentSyncSaveOptions::Supported(true)),
+                        ..Default::default()
+                    },
                 )),
                 execute_command_provider: Some(ExecuteCommandOptions {
                     commands: vec![APPLY_ALL_FIXES_COMMAND.to_owned()],
                     work_done_progress_options: Default::default(),
                 }),
+                code_action_provider: Some(CodeActionProviderCapability::Simple(true)),
+                hover_provider: Some(HoverProviderCapability::Simple(true)),
+                diagnostic_provider: Some(DiagnosticServerCapabilities::Options(
+                    DiagnosticOptions {
+                        identifier: Some(DIAGNOSTIC_SOURCE.to_owned()),
+                        inter_file_dependencies: false,
+                        workspace_diagnostics,
+                        work_done_progress_options: Default::default(),
+                    },
+                )),
+                workspace: Some(WorkspaceServerCapabilities {
+                    workspace_folders: Some(WorkspaceFoldersServerCapabilities {
+                        supported: Some(true),
+                        change_notifications: Some(OneOf::Left(true)),
+                    }),
+                    file_operations: None,
+                }),
                 ..Default::default()
             },
             ..Default::default()
@@ -264,6 +501,35 @@ impl<TLocalLinter: LocalLinter + 'static> LanguageServer for Backend<TLocalLinte
         // self.client
         //     .log_message(tower_lsp::lsp_types::MessageType::INFO, "server initialized!")
         //     .await;
+        if self.are_workspace_diagnostics_enabled() && !self.uses_pull_diagnostics() {
+            for workspace_folder in self.workspace_folders.lock().await.iter() {
+                self.spawn_workspace_folder_scan(workspace_folder.clone());
+            }
+        }
+    }
+
+    async fn did_change_workspace_folders(&self, params: DidChangeWorkspaceFoldersParams) {
+        let added = params
+            .event
+            .added
+            .into_iter()
+            .filter_map(|workspace_folder| workspace_folder.uri.to_file_path().ok())
+            .collect::<Vec<_>>();
+        {
+            let mut workspace_folders = self.workspace_folders.lock().await;
+            for removed in params.event.removed {
+                if let Ok(removed) = removed.uri.to_file_path() {
+                    workspace_folders.retain(|workspace_folder| *workspace_folder != removed);
+                }
+            }
+            workspace_folders.extend(added.iter().cloned());
+        }
+        self.invalidate_workspace_diagnostics().await;
+        if self.are_workspace_diagnostics_enabled() && !self.uses_pull_diagnostics() {
+            for workspace_folder in added {
+                self.spawn_workspace_folder_scan(workspace_folder);
+            }
+        }
     }
 
     async fn shutdown(&self) -> Result<()> {
@@ -284,6 +550,7 @@ impl<TLocalLinter: LocalLinter + 'static> LanguageServer for Backend<TLocalLinte
                 ),
                 contents,
                 last_fixing_run_violations: Default::default(),
+                last_violations: Default::default(),
                 supported_language_language,
             },
         );
@@ -353,6 +620,14 @@ impl<TLocalLinter: LocalLinter + 'static> LanguageServer for Backend<TLocalLinte
             .await;
     }
 
+    async fn did_save(&self, _: DidSaveTextDocumentParams) {
+        self.invalidate_workspace_diagnostics().await;
+    }
+
+    async fn did_change_watched_files(&self, _: DidChangeWatchedFilesParams) {
+        self.invalidate_workspace_diagnostics().await;
+    }
+
     async fn execute_command(
         &self,
         params: ExecuteCommandParams,
@@ -367,6 +642,146 @@ impl<TLocalLinter: LocalLinter + 'static> LanguageServer for Backend<TLocalLinte
 
         Ok(None)
     }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let uri = params.text_document.uri;
+        let code_actions = params
+            .context
+            .diagnostics
+            .into_iter()
+            .filter(|diagnostic| diagnostic.source.as_deref() == Some(DIAGNOSTIC_SOURCE))
+            .filter_map(|diagnostic| {
+                let edits: Vec<TextEdit> = serde_json::from_value(diagnostic.data.clone()?).ok()?;
+                Some(CodeActionOrCommand::CodeAction(CodeAction {
+                    title: match diagnostic.code.as_ref() {
+                        Some(NumberOrString::String(rule_name)) => {
+                            format!("Fix this {rule_name} problem")
+                        }
+                        _ => "Fix this problem".to_owned(),
+                    },
+                    kind: Some(CodeActionKind::QUICKFIX),
+                    edit: Some(WorkspaceEdit {
+                        changes: Some([(uri.clone(), edits)].into()),
+                        ..Default::default()
+                    }),
+                    diagnostics: Some(vec![diagnostic]),
+                    is_preferred: Some(true),
+                    ..Default::default()
+                }))
+            })
+            .collect::<CodeActionResponse>();
+        Ok(Some(code_actions))
+    }
+
+    async fn diagnostic(
+        &self,
+        params: DocumentDiagnosticParams,
+    ) -> Result<DocumentDiagnosticReportResult> {
+        let diagnostics = self.run_linting(&params.text_document.uri).await;
+        Ok(
+            DocumentDiagnosticReport::Full(RelatedFullDocumentDiagnosticReport {
+                related_documents: None,
+                full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                    result_id: None,
+                    items: diagnostics,
+                },
+            })
+            .into(),
+        )
+    }
+
+    // Clients keep re-issuing workspace diagnostic pulls, so the workspace
+    // only gets rescanned after something changed
+    async fn workspace_diagnostic(
+        &self,
+        params: WorkspaceDiagnosticParams,
+    ) -> Result<WorkspaceDiagnosticReportResult> {
+        if !self.are_workspace_diagnostics_enabled() {
+            return Ok(WorkspaceDiagnosticReport {
+                items: Default::default(),
+            }
+            .into());
+        }
+        let previous_result_ids = params
+            .previous_result_ids
+            .into_iter()
+            .map(|previous_result_id| (previous_result_id.uri, previous_result_id.value))
+            .collect::<HashMap<_, _>>();
+        let mut workspace_diagnostics = self.workspace_diagnostics.lock().await;
+        if workspace_diagnostics.is_none() {
+            *workspace_diagnostics = Some(self.lint_workspace_folders().await);
+        }
+        let per_file = self.per_file.lock().await;
+        let items = workspace_diagnostics
+            .as_ref()
+            .unwrap()
+            .iter()
+            // open files get reported through document diagnostics instead
+            .filter(|(uri, _)| !per_file.contains_key(*uri))
+            .map(|(uri, (result_id, diagnostics))| {
+                if previous_result_ids.get(uri) == Some(result_id) {
+                    WorkspaceDocumentDiagnosticReport::Unchanged(
+                        WorkspaceUnchangedDocumentDiagnosticReport {
+                            uri: uri.clone(),
+                            version: None,
+                            unchanged_document_diagnostic_report:
+                                UnchangedDocumentDiagnosticReport {
+                                    result_id: result_id.clone(),
+                                },
+                        },
+                    )
+                } else {
+                    WorkspaceDocumentDiagnosticReport::Full(WorkspaceFullDocumentDiagnosticReport {
+                        uri: uri.clone(),
+                        version: None,
+                        full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                            result_id: Some(result_id.clone()),
+                            items: diagnostics.clone(),
+                        },
+                    })
+                }
+            })
+            .collect();
+        Ok(WorkspaceDiagnosticReport { items }.into())
+    }
+
+    async fn hover(&self, params: HoverParams) -> Result<Option<Hover>> {
+        let uri = &params.text_document_position_params.text_document.uri;
+        let position = params.text_document_position_params.position;
+        let per_config_state = self
+            .per_config
+            .lock()
+            .await
+            .get(&get_config_file_path(uri))
+            .cloned();
+        let config = per_config_state
+            .as_ref()
+            .map(|per_config_state| &per_config_state.config);
+        let per_file = self.per_file.lock().await;
+        let Some(per_file_state) = per_file.get(uri) else {
+            return Ok(None);
+        };
+        let hovered_violations_docs = per_file_state
+            .last_violations
+            .iter()
+            .filter(|violation| {
+                let range =
+                    tree_sitter_range_to_lsp_range(&per_file_state.contents, violation.range);
+                range.start <= position && position <= range.end
+            })
+            .map(|violation| get_violation_hover_markdown(violation, config))
+            .collect::<Vec<_>>();
+        if hovered_violations_docs.is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: hovered_violations_docs.join("\n\n---\n\n"),
+            }),
+            range: None,
+        }))
+    }
 }
 
 #[derive(Debug)]
@@ -375,6 +790,7 @@ struct PerFileState {
     tree: Tree,
     edits_since_last_fixing_run: AccumulatedEditsOrEntireFileChanged,
     last_fixing_run_violations: Option<Vec<ViolationWithContext>>,
+    last_violations: Vec<ViolationWithContext>,
     supported_language_language: SupportedLanguageLanguage,
 }
 
@@ -445,6 +861,109 @@ fn tree_sitter_range_to_lsp_range(file_contents: &Rope, range: tree_sitter::Rang
     byte_offset_range_to_lsp_range(file_contents, range.start_byte..range.end_byte)
 }
 
+fn violation_to_diagnostic(
+    violation: &ViolationWithContext,
+    file_contents: &Rope,
+    uri: &Url,
+) -> Diagnostic {
+    Diagnostic {
+        message: violation.message().into_owned(),
+        range: tree_sitter_range_to_lsp_range(file_contents, violation.range),
+        severity: Some(match violation.level {
+            ErrorLevel::Warn => DiagnosticSeverity::WARNING,
+            _ => DiagnosticSeverity::ERROR,
+        }),
+        code: Some(NumberOrString::String(violation.rule.name.clone())),
+        code_description: violation
+            .rule
+            .docs_url
+            .as_ref()
+            .and_then(|docs_url| Url::parse(docs_url).ok())
+            .map(|href| CodeDescription { href }),
+        source: Some(DIAGNOSTIC_SOURCE.to_owned()),
+        related_information: violation
+            .related_locations
+            .as_ref()
+            .map(|related_locations| {
+                related_locations
+                    .iter()
+                    .map(|related_location| DiagnosticRelatedInformation {
+                        location: Location {
+                            uri: uri.clone(),
+                            range: tree_sitter_range_to_lsp_range(
+                                file_contents,
+                                related_location.range,
+                            ),
+                        },
+                        message: related_location.message.clone(),
+                    })
+                    .collect()
+            }),
+        tags: Some(
+            violation
+                .rule
+                .tags
+                .iter()
+                .map(|tag| match tag {
+                    RuleTag::Unnecessary => DiagnosticTag::UNNECESSARY,
+                    RuleTag::Deprecated => DiagnosticTag::DEPRECATED,
+                })
+                .collect::<Vec<_>>(),
+        )
+        .filter(|tags| !tags.is_empty()),
+        data: violation.fixes.as_ref().map(|fixes| {
+            serde_json::to_value(
+                fixes
+                    .iter()
+                    .map(|fix| TextEdit {
+                        range: tree_sitter_range_to_lsp_range(file_contents, fix.range),
+                        new_text: fix.replacement.clone(),
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .unwrap()
+        }),
+    }
+}
+
+fn get_violation_hover_markdown(
+    violation: &ViolationWithContext,
+    config: Option<&Config>,
+) -> String {
+    let rule_name = config.map_or_else(
+        || violation.rule.name.clone(),
+        |config| violation.plugin_prefixed_rule_name(config),
+    );
+    let mut markdown = match violation.rule.docs_url.as_ref() {
+        Some(docs_url) => format!("**[{rule_name}]({docs_url})**"),
+        None => format!("**{rule_name}**"),
+    };
+    let docs = violation.rule.docs.as_ref();
+    if let Some(description) = docs.and_then(|docs| docs.description.as_ref()) {
+        markdown.push_str(&format!("\n\n{description}"));
+    }
+    markdown.push_str(&format!(
+        "\n\nFixable: {}",
+        if violation.rule.fixable { "yes" } else { "no" }
+    ));
+    if let Some(options) = config.and_then(|config| {
+        config
+            .rule_configurations
+            .iter()
+            .find(|rule_configuration| rule_configuration.name == rule_name)
+            .and_then(|rule_configuration| rule_configuration.options.as_ref())
+    }) {
+        markdown.push_str(&format!(
+            "\n\nConfigured options:\n```json\n{}\n```",
+            serde_json::to_string_pretty(options).unwrap()
+        ));
+    }
+    if let Some(docs) = docs {
+        push_examples_markdown(&mut markdown, docs);
+    }
+    markdown
+}
+
 fn get_text_document_edits(
     edits: &AccumulatedEdits,
     uri: &Url,
@@ -480,20 +999,74 @@ fn get_uri_from_arguments(arguments: &[serde_json::Value]) -> Url {
     }
 }
 
-fn get_supported_language_language(uri: &Url) -> SupportedLanguageLanguage {
-    // TODO: how should this actually work?
-    let uri = uri.as_str();
-    if uri.ends_with(".rs") {
-        SupportedLanguageLanguage::Rust
-    } else if uri.ends_with(".ts") {
-        SupportedLanguageLanguage::Typescript
-    } else if uri.ends_with(".tsx") {
-        SupportedLanguageLanguage::Tsx
-    } else if uri.ends_with(".js") || uri.ends_with(".jsx") {
-        SupportedLanguageLanguage::Javascript
-    } else {
-        SupportedLanguageLanguage::Rust
+fn get_config_file_path(uri: &Url) -> Option<PathBuf> {
+    find_config_file_from(uri.to_file_path().ok()?.parent()?)
+}
+
+fn lint_workspace_folder<TLocalLinter: LocalLinter + 'static>(
+    local_linter: Arc<TLocalLinter>,
+    workspace_folder: PathBuf,
+) -> UnboundedReceiver<std::result::Result<(PathBuf, Vec<ViolationWithContext>), Error>> {
+    let (sender, receiver) = mpsc::unbounded_channel();
+    // workspace folders that no config file applies to (in them or in an
+    // ancestor directory) get skipped
+    if let Some(config_file_path) = find_config_file_from(&workspace_folder) {
+        tokio::task::spawn_blocking(move || {
+            if let Err(error) = local_linter.run_with_per_file_callback(
+                Args {
+                    paths: vec![workspace_folder],
+                    config: Some(config_file_path),
+                    ..Default::default()
+                },
+                |path, violations| {
+                    let _ = sender.send(Ok((path.to_owned(), violations)));
+                },
+            ) {
+                let _ = sender.send(Err(error));
+            }
+        });
+    }
+    receiver
+}
+
+async fn show_error(client: &Client, shown_errors: &Mutex<HashSet<String>>, error: &Error) {
+    let message = format!("{DIAGNOSTIC_SOURCE}: {error}");
+    if !shown_errors.lock().await.insert(message.clone()) {
+        return;
+    }
+    client.show_message(MessageType::ERROR, message).await;
+}
+
+async fn get_closed_file_diagnostics(
+    per_file: &Mutex<HashMap<Url, PerFileState>>,
+    path: &Path,
+    violations: &[ViolationWithContext],
+) -> Option<(Url, Vec<Diagnostic>)> {
+    let uri = Url::from_file_path(path).ok()?;
+    if per_file.lock().await.contains_key(&uri) {
+        return None;
     }
+    let file_contents: Rope = (&*fs::read_to_string(path).ok()?).into();
+    let diagnostics = violations
+        .iter()
+        .map(|violation| violation_to_diagnostic(violation, &file_contents, &uri))
+        .collect();
+    Some((uri, diagnostics))
+}
+
+// So that a rescan doesn't change the result ids of files whose diagnostics
+// didn't change
+fn get_result_id(diagnostics: &[Diagnostic]) -> String {
+    let mut hasher = DefaultHasher::new();
+    serde_json::to_string(diagnostics)
+        .unwrap()
+        .hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
+
+fn get_supported_language_language(uri: &Url) -> SupportedLanguageLanguage {
+    get_supported_language_language_for_path(Path::new(uri.path()))
+        .unwrap_or(SupportedLanguageLanguage::Rust)
 }
 
 pub async fn run<TLocalLinter: LocalLinter + 'static>(
@@ -2,7 +2,7 @@ use std::{collections::HashMap, sync::Arc};
 
 use derive_builder::Builder;
 
-use crate::{configuration::Configuration, Rule};
+use crate::{configuration::Configuration, Formatter, Rule};
 
 #[non_exhaustive]
 #[derive(Builder, Clone)]
@@ -13,4 +13,6 @@ pub struct Plugin {
     pub rules: Vec<Arc<dyn Rule>>,
     #[builder(default)]
     pub configs: HashMap<String, Configuration>,
+    #[builder(default)]
+    pub formatters: HashMap<String, Arc<dyn Formatter>>,
 }
@@ -1,5 +1,6 @@
-use std::{collections::HashMap, ops, sync::Arc};
+use std::{collections::HashMap, fmt, ops, sync::Arc};
 
+use serde::de::DeserializeOwned;
 use tracing::{instrument, trace_span};
 use tree_sitter_grep::{
     tree_sitter::{QueryError, QueryMatch},
@@ -7,7 +8,7 @@ use tree_sitter_grep::{
 };
 
 use crate::{
-    config::{PluginIndex, RuleConfiguration},
+    config::{ErrorLevel, PluginIndex, RuleConfiguration},
     context::{FileRunContext, QueryMatchContext},
     tree_sitter::{Language, Node, Query},
     Config,
@@ -21,6 +22,31 @@ pub struct RuleMeta {
     pub messages: Option<HashMap<String, String>>,
     pub allow_self_conflicting_fixes: bool,
     pub concatenate_adjacent_insert_fixes: bool,
+    pub docs_url: Option<String>,
+    pub tags: Vec<RuleTag>,
+    pub docs: Option<RuleDocs>,
+    // JSON Schema for the rule's options
+    pub options_schema: Option<RuleOptions>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RuleDocs {
+    pub description: Option<String>,
+    pub examples: RuleExamples,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct RuleExamples {
+    pub correct: Vec<String>,
+    pub incorrect: Vec<String>,
+    // parallel to `incorrect`
+    pub incorrect_fixed_outputs: Vec<Option<String>>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RuleTag {
+    Unnecessary,
+    Deprecated,
 }
 
 pub trait Rule: Send + Sync {
@@ -30,6 +56,12 @@ pub trait Rule: Send + Sync {
         config: &Config,
         rule_configuration: &RuleConfiguration,
     ) -> Arc<dyn RuleInstance>;
+    fn validate_options(&self, _options: Option<&RuleOptions>) -> Result<(), RuleOptionsError> {
+        Ok(())
+    }
+    fn accepts_options(&self) -> bool {
+        true
+    }
 }
 
 pub trait RuleInstance: Send + Sync {
@@ -47,6 +79,9 @@ pub struct InstantiatedRule {
     pub rule: Arc<dyn Rule>,
     pub rule_instance: Arc<dyn RuleInstance>,
     pub plugin_index: Option<PluginIndex>,
+    // index into the plugin's rules (or the standalone rules)
+    pub rule_index: usize,
+    pub level: ErrorLevel,
 }
 
 impl InstantiatedRule {
@@ -59,8 +94,23 @@ impl InstantiatedRule {
         Self {
             meta: rule.meta(),
             rule_instance: rule.clone().instantiate(config, rule_configuration),
+            rule_index: config.get_rule_index(&rule, plugin_index),
             rule,
             plugin_index,
+            level: rule_configuration.level,
+        }
+    }
+
+    pub fn plugin_prefixed_name(&self, config: &Config) -> String {
+        match self.plugin_index {
+            None => self.meta.name.clone(),
+            Some(plugin_index) => {
+                format!(
+                    "{}/{}",
+                    config.get_plugin_name(plugin_index),
+                    self.meta.name
+                )
+            }
         }
     }
 }
@@ -198,3 +248,50 @@ pub struct ResolvedRuleListenerQuery {
 }
 
 pub type RuleOptions = serde_json::Value;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuleOptionsError {
+    // keys/indices leading to the offending value within the rule's options
+    pub path: Vec<String>,
+    pub message: String,
+}
+
+impl RuleOptionsError {
+    pub fn new(message: impl Into<String>) -> Self {
+        Self {
+            path: Default::default(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for RuleOptionsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.path.is_empty() {
+            write!(f, "at `{}`: ", self.path.join("."))?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for RuleOptionsError {}
+
+pub fn validate_rule_options<TOptions: DeserializeOwned>(
+    options: &RuleOptions,
+) -> Result<(), RuleOptionsError> {
+    serde_path_to_error::deserialize::<_, TOptions>(options.clone())
+        .map(|_| ())
+        .map_err(|error| RuleOptionsError {
+            path: error
+                .path()
+                .iter()
+                .filter_map(|segment| match segment {
+                    serde_path_to_error::Segment::Seq { index } => Some(index.to_string()),
+                    serde_path_to_error::Segment::Map { key } => Some(key.clone()),
+                    serde_path_to_error::Segment::Enum { variant } => Some(variant.clone()),
+                    serde_path_to_error::Segment::Unknown => None,
+                })
+                .collect(),
+            message: error.into_inner().to_string(),
+        })
+}
@@ -9,7 +9,7 @@ use crate::{
     config::{ConfigBuilder, ErrorLevel},
     context::FromFileRunContextInstanceProvider,
     environment::{deep_merged, Environment},
-    rule::{Rule, RuleOptions},
+    rule::{Rule, RuleExamples, RuleOptions},
     violation::{MessageOrMessageId, ViolationData, ViolationWithContext},
     FileRunContext, FixingForSliceRunStatus, FromFileRunContextInstanceProviderFactory, Plugin,
     RuleConfiguration, SliceRunStatus,
@@ -276,7 +276,9 @@ impl RuleTester {
                 supported_language_language,
                 &*self.from_file_run_context_instance_provider_factory,
                 None,
-            );
+            )
+            .unwrap_or_else(|error| panic!("{error}"));
+            panic_on_internal_errors(&violations);
 
             if self.should_aggregate_results {
                 self.aggregated_results
@@ -358,7 +360,9 @@ impl RuleTester {
                 supported_language_language,
                 &*self.from_file_run_context_instance_provider_factory,
                 Default::default(),
-            );
+            )
+            .unwrap_or_else(|error| panic!("{error}"));
+            panic_on_internal_errors(&violations);
 
             if !self.check_that_violations_match_expected(
                 &violations,
@@ -660,6 +664,15 @@ impl RuleTester {
     }
 }
 
+fn panic_on_internal_errors(violations: &[ViolationWithContext]) {
+    if let Some(violation) = violations
+        .iter()
+        .find(|violation| violation.is_internal_error)
+    {
+        panic!("{}", violation.message());
+    }
+}
+
 pub fn compare_ranges(a: Range, b: Range) -> Ordering {
     match a.start_byte.cmp(&b.start_byte) {
         Ordering::Equal => {}
@@ -690,6 +703,33 @@ impl RuleTests {
     }
 }
 
+impl From<RuleTests> for RuleExamples {
+    fn from(value: RuleTests) -> Self {
+        let (incorrect, incorrect_fixed_outputs) = value
+            .invalid_tests
+            .into_iter()
+            .map(|invalid_test| {
+                (
+                    invalid_test.code,
+                    match invalid_test.output {
+                        Some(RuleTestExpectedOutput::Output(output)) => Some(output),
+                        _ => None,
+                    },
+                )
+            })
+            .unzip();
+        Self {
+            correct: value
+                .valid_tests
+                .into_iter()
+                .map(|valid_test| valid_test.code)
+                .collect(),
+            incorrect,
+            incorrect_fixed_outputs,
+        }
+    }
+}
+
 #[derive(Builder, Debug)]
 #[builder(setter(strip_option, into))]
 pub struct RuleTestValid {
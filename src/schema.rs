@@ -0,0 +1,116 @@
+use std::sync::Arc;
+
+use serde_json::{json, Map, Value};
+
+use crate::{Config, Rule};
+
+pub fn get_config_schema(config: &Config) -> Value {
+    let rules = config
+        .get_rules_by_plugin_prefixed_name()
+        .iter()
+        .map(|(rule_name, (rule, _))| (rule_name.clone(), get_rule_schema(rule)))
+        .collect::<Map<_, _>>();
+    let mut plugin_names = config
+        .all_plugins()
+        .iter()
+        .map(|plugin| plugin.name.clone())
+        .collect::<Vec<_>>();
+    plugin_names.sort();
+    let mut configuration_references = config
+        .all_plugins()
+        .iter()
+        .flat_map(|plugin| {
+            plugin
+                .configs
+                .keys()
+                .map(|configuration_name| format!("{}/{configuration_name}", plugin.name))
+        })
+        .collect::<Vec<_>>();
+    configuration_references.sort();
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "tree-sitter-lint config",
+        "type": "object",
+        "additionalProperties": false,
+        "properties": {
+            "root": { "type": "boolean" },
+            "plugins": {
+                "type": "object",
+                "properties": plugin_names
+                    .into_iter()
+                    .map(|plugin_name| (plugin_name, json!({ "$ref": "#/definitions/pluginSpec" })))
+                    .collect::<Map<_, _>>(),
+                "additionalProperties": { "$ref": "#/definitions/pluginSpec" },
+            },
+            "extends": {
+                "type": "array",
+                "items": {
+                    "anyOf": [
+                        { "enum": configuration_references },
+                        // relative to the extending config file
+                        { "type": "string", "pattern": "\\.ya?ml$" },
+                    ],
+                },
+            },
+            "rules": {
+                "type": "object",
+                "properties": rules,
+                "additionalProperties": false,
+            },
+            "tree-sitter-lint-dependency": {
+                "type": "object",
+                "required": ["path"],
+                "additionalProperties": false,
+                "properties": {
+                    "path": { "type": "string" },
+                },
+            },
+        },
+        "definitions": {
+            "level": {
+                // should match the (camel-cased) ErrorLevel variants
+                "enum": ["error", "warn", "off"],
+            },
+            "pluginSpec": {
+                "type": "object",
+                "additionalProperties": false,
+                "properties": {
+                    "path": { "type": "string" },
+                    "version": { "type": "string" },
+                    "git": { "type": "string" },
+                    "rev": { "type": "string" },
+                    "tag": { "type": "string" },
+                    "branch": { "type": "string" },
+                    "package": { "type": "string" },
+                },
+                "anyOf": [
+                    { "required": ["path"] },
+                    { "required": ["version"] },
+                    { "required": ["git"] },
+                ],
+            },
+        },
+    })
+}
+
+fn get_rule_schema(rule: &Arc<dyn Rule>) -> Value {
+    let meta = rule.meta();
+    let mut schema = json!({
+        "type": "object",
+        "required": ["level"],
+        "additionalProperties": false,
+        "properties": {
+            "level": { "$ref": "#/definitions/level" },
+            "options": meta.options_schema.clone().unwrap_or_else(|| json!({})),
+        },
+    });
+    if let Some(description) = meta
+        .docs
+        .as_ref()
+        .and_then(|docs| docs.description.as_ref())
+    {
+        schema["description"] = description.trim().into();
+    }
+    schema
+}
@@ -19,6 +19,16 @@ impl<'a> MutRopeOrSlice<'a> {
             }
         }
     }
+
+    pub(crate) fn range_contains_byte(&self, range: ops::Range<usize>, byte: u8) -> bool {
+        match self {
+            MutRopeOrSlice::Rope(rope) => rope
+                .bytes_at(range.start)
+                .take(range.len())
+                .any(|rope_byte| rope_byte == byte),
+            MutRopeOrSlice::Slice(slice) => slice[range].contains(&byte),
+        }
+    }
 }
 
 impl<'a> From<&'a mut Rope> for MutRopeOrSlice<'a> {
@@ -0,0 +1,131 @@
+use std::path::Path;
+
+use super::reports_functions_rule;
+use crate::{
+    baseline::{get_fingerprint, Baseline, BaselineDirectory},
+    rule_tester::DummyFromFileRunContextInstanceProviderFactory,
+    run_for_slice,
+    tree_sitter_grep::SupportedLanguageLanguage,
+    Config, ConfigBuilder, ViolationWithContext,
+};
+
+fn get_config() -> Config {
+    ConfigBuilder::default()
+        .all_standalone_rules([reports_functions_rule()])
+        .default_rule_configurations()
+        .build()
+        .unwrap()
+}
+
+fn get_baseline_directory() -> BaselineDirectory {
+    BaselineDirectory::new(Path::new("baseline.json")).unwrap()
+}
+
+fn get_violations(file_contents: &str, config: &Config) -> Vec<ViolationWithContext> {
+    run_for_slice(
+        file_contents.as_bytes(),
+        None,
+        "./src/whee.rs",
+        config,
+        SupportedLanguageLanguage::Rust,
+        &DummyFromFileRunContextInstanceProviderFactory,
+        None,
+    )
+    .unwrap()
+    .violations
+}
+
+#[test]
+fn test_baseline_ignores_moved_violations() {
+    let config = get_config();
+    let baseline_directory = get_baseline_directory();
+    let baseline = Baseline::from_violations(
+        &get_violations("fn whee() {}\nfn foo() { }\n", &config),
+        &config,
+        &baseline_directory,
+    );
+
+    let (new_violations, stale_entries) = baseline.filter(
+        get_violations(
+            "use std::fs;\n\nfn whee() {}\nfn   foo() {\n}\nfn bar() {}\n",
+            &config,
+        ),
+        &config,
+        &baseline_directory,
+    );
+    assert_eq!(new_violations.len(), 1);
+    assert_eq!(new_violations[0].range.start_point.row, 5);
+    assert!(stale_entries.is_empty());
+}
+
+#[test]
+fn test_baseline_stale_entries() {
+    let config = get_config();
+    let baseline_directory = get_baseline_directory();
+    let baseline = Baseline::from_violations(
+        &get_violations("fn whee() {}\nfn whee() {}\nfn foo() {}\n", &config),
+        &config,
+        &baseline_directory,
+    );
+
+    let (new_violations, stale_entries) = baseline.filter(
+        get_violations("fn whee() {}\n", &config),
+        &config,
+        &baseline_directory,
+    );
+    assert!(new_violations.is_empty());
+    let mut expected = ["fn whee() {}", "fn foo() {}"]
+        .into_iter()
+        .map(|node_text| {
+            format!(
+                r#""src/whee.rs" function reports-functions (fingerprint {:016x})"#,
+                get_fingerprint(node_text)
+            )
+        })
+        .collect::<Vec<_>>();
+    expected.sort();
+    assert_eq!(
+        stale_entries
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>(),
+        expected
+    );
+}
+
+#[test]
+fn test_baseline_paths_are_relative_to_the_baseline_file() {
+    let config = get_config();
+    let violations = get_violations("fn whee() {}\n", &config);
+    let baseline = Baseline::from_violations(
+        &violations,
+        &config,
+        &BaselineDirectory::new(Path::new("src/baseline.json")).unwrap(),
+    );
+    assert_eq!(
+        serde_json::to_value(&baseline).unwrap()["entries"][0]["path"],
+        "whee.rs"
+    );
+
+    let baseline = Baseline::from_violations(
+        &violations,
+        &config,
+        &BaselineDirectory::new(Path::new("tests/baseline.json")).unwrap(),
+    );
+    assert_eq!(
+        serde_json::to_value(&baseline).unwrap()["entries"][0]["path"],
+        "../src/whee.rs"
+    );
+}
+
+#[test]
+fn test_fingerprint_ignores_whitespace() {
+    assert_eq!(
+        get_fingerprint("fn whee() {\n    foo();\n}"),
+        get_fingerprint("fn whee() { foo(); }")
+    );
+    assert_ne!(
+        get_fingerprint("fn whee() { foo(); }"),
+        get_fingerprint("fn whee() { bar(); }")
+    );
+}
@@ -0,0 +1,89 @@
+use std::path::Path;
+
+use crate::{cli::get_plugin_dependency, config::PluginSpecValue, Error};
+
+fn get_dependency(plugin_spec: PluginSpecValue) -> Result<String, Error> {
+    get_plugin_dependency("whee", &plugin_spec, &Path::new("..").join(".."))
+}
+
+#[test]
+fn test_plugin_dependency_path() {
+    assert_eq!(
+        get_dependency(PluginSpecValue {
+            path: Some("plugins/whee".into()),
+            ..Default::default()
+        })
+        .unwrap(),
+        "tree-sitter-lint-plugin-whee = { path = \"../../plugins/whee\" }\n"
+    );
+}
+
+#[test]
+fn test_plugin_dependency_version_and_package() {
+    assert_eq!(
+        get_dependency(PluginSpecValue {
+            version: Some("0.3".to_owned()),
+            package: Some("whee-lint".to_owned()),
+            ..Default::default()
+        })
+        .unwrap(),
+        "tree-sitter-lint-plugin-whee = { package = \"whee-lint\", version = \"0.3\" }\n"
+    );
+}
+
+#[test]
+fn test_plugin_dependency_git() {
+    assert_eq!(
+        get_dependency(PluginSpecValue {
+            git: Some("https://github.com/whee/whee".to_owned()),
+            tag: Some("v1.0.0".to_owned()),
+            ..Default::default()
+        })
+        .unwrap(),
+        "tree-sitter-lint-plugin-whee = { git = \"https://github.com/whee/whee\", tag = \"v1.0.0\" }\n"
+    );
+}
+
+#[test]
+fn test_plugin_dependency_invalid() {
+    for plugin_spec in [
+        Default::default(),
+        PluginSpecValue {
+            version: Some("0.3".to_owned()),
+            tag: Some("v1.0.0".to_owned()),
+            ..Default::default()
+        },
+        PluginSpecValue {
+            git: Some("https://github.com/whee/whee".to_owned()),
+            rev: Some("abc123".to_owned()),
+            branch: Some("main".to_owned()),
+            ..Default::default()
+        },
+        PluginSpecValue {
+            path: Some("plugins/whee".into()),
+            git: Some("https://github.com/whee/whee".to_owned()),
+            ..Default::default()
+        },
+    ] {
+        let error = get_dependency(plugin_spec).err().unwrap();
+        assert!(
+            matches!(&error, Error::InvalidPluginSpec { plugin_name, .. } if plugin_name == "whee"),
+            "{error:?}"
+        );
+    }
+}
+
+#[test]
+fn test_plugin_dependency_escapes_values() {
+    assert_eq!(
+        get_dependency(PluginSpecValue {
+            git: Some(r#"https://example.com/"whee"\whee"#.to_owned()),
+            ..Default::default()
+        })
+        .unwrap(),
+        concat!(
+            r#"tree-sitter-lint-plugin-whee = { git = "https://example.com/\"whee\"\\whee" }"#,
+            "\n"
+        )
+    );
+}
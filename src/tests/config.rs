@@ -0,0 +1,365 @@
+use std::{collections::BTreeMap, fs, path::Path, sync::Arc};
+
+use clap::Parser;
+use proc_macros::rule_crate_internal as rule;
+use serde_json::json;
+
+use super::{reports_functions_rule, reports_structs_rule, write_config_file};
+use crate::{
+    get_supported_language_language_for_path, rule::Rule,
+    rule_tester::DummyFromFileRunContextInstanceProviderFactory, run,
+    tree_sitter_grep::SupportedLanguageLanguage, Args, ArgsBuilder, ConfigurationBuilder,
+    ErrorLevel, PluginBuilder, RuleConfigurationValueBuilder,
+};
+
+#[test]
+fn test_effective_config() {
+    let takes_count: Arc<dyn Rule> = rule! {
+        name => "takes-count",
+        options_type => Option<usize>,
+        listeners => [
+            r#"(function_item) @c"# => |node, context| {}
+        ],
+        languages => [Rust],
+    };
+    let no_foo: Arc<dyn Rule> = rule! {
+        name => "no-foo",
+        listeners => [
+            r#"(function_item) @c"# => |node, context| {}
+        ],
+        languages => [Rust],
+    };
+    let js_only: Arc<dyn Rule> = rule! {
+        name => "js-only",
+        listeners => [
+            r#"(function_declaration) @c"# => |node, context| {}
+        ],
+        languages => [Javascript],
+    };
+    let error_level = || {
+        RuleConfigurationValueBuilder::default()
+            .level(ErrorLevel::Error)
+            .build()
+            .unwrap()
+    };
+    let plugin = PluginBuilder::default()
+        .name("whee")
+        .rules([no_foo, js_only])
+        .configs([
+            (
+                "base".to_owned(),
+                ConfigurationBuilder::default()
+                    .rules([
+                        ("whee/no-foo".to_owned(), error_level()),
+                        ("whee/js-only".to_owned(), error_level()),
+                    ])
+                    .build()
+                    .unwrap(),
+            ),
+            (
+                "recommended".to_owned(),
+                ConfigurationBuilder::default()
+                    .extends(["whee/base".to_owned()])
+                    .build()
+                    .unwrap(),
+            ),
+        ])
+        .build()
+        .unwrap();
+    let path = write_config_file(
+        "effective-config",
+        "plugins: {}\nextends: [whee/recommended]\nrules:\n  takes-count:\n    level: error\n    options: 3\n",
+    );
+    let config = ArgsBuilder::default()
+        .config(path)
+        .build()
+        .unwrap()
+        .load_config_file_and_into_config(vec![plugin], vec![takes_count])
+        .ok()
+        .unwrap();
+
+    let effective_config = config.get_effective_config(Some(Path::new("src/foo.rs")));
+    assert_eq!(effective_config["languages"], json!(["Rust"]));
+    assert_eq!(
+        effective_config["rules"],
+        json!({
+            "takes-count": {
+                "level": "error",
+                "options": 3,
+                "origin": "configFile",
+            },
+            "whee/no-foo": {
+                "level": "error",
+                "options": null,
+                "origin": { "extends": ["whee/recommended", "whee/base"] },
+            },
+        })
+    );
+
+    let effective_config = config.get_effective_config(None);
+    assert_eq!(effective_config["languages"], json!(["Javascript", "Rust"]));
+    assert!(effective_config["rules"].get("whee/js-only").is_some());
+    assert!(effective_config.get("linted").is_none());
+
+    let effective_config = config.get_effective_config(Some(Path::new("src/foo.rs")));
+    assert_eq!(effective_config["linted"], json!(true));
+
+    let effective_config = config.get_effective_config(Some(Path::new("src/foo.txt")));
+    assert_eq!(effective_config["linted"], json!(false));
+    assert_eq!(effective_config["languages"], json!([]));
+    assert_eq!(effective_config["rules"], json!({}));
+}
+
+#[test]
+fn test_get_supported_language_language_for_path() {
+    for (path, expected) in [
+        ("src/foo.rs", Some(SupportedLanguageLanguage::Rust)),
+        ("src/foo.ts", Some(SupportedLanguageLanguage::Typescript)),
+        ("src/foo.tsx", Some(SupportedLanguageLanguage::Tsx)),
+        ("src/foo.jsx", Some(SupportedLanguageLanguage::Javascript)),
+        ("src/foo.txt", None),
+        ("src/foo", None),
+    ] {
+        assert_eq!(
+            get_supported_language_language_for_path(Path::new(path)),
+            expected,
+            "{path}"
+        );
+    }
+}
+
+#[test]
+fn test_print_config_arg() {
+    let args = Args::parse_from(["tree-sitter-lint", "--print-config"]);
+    assert_eq!(args.print_config, Some(None));
+
+    let args = Args::parse_from(["tree-sitter-lint", "--print-config", "src/foo.rs"]);
+    assert_eq!(args.print_config, Some(Some("src/foo.rs".into())));
+}
+
+#[test]
+fn test_stdin_args() {
+    let args = Args::parse_from([
+        "tree-sitter-lint",
+        "--stdin",
+        "--stdin-filename",
+        "src/foo.rs",
+    ]);
+    assert!(args.stdin);
+    assert_eq!(args.stdin_filename, Some("src/foo.rs".into()));
+
+    assert!(Args::try_parse_from(["tree-sitter-lint", "--stdin"]).is_err());
+    assert!(Args::try_parse_from([
+        "tree-sitter-lint",
+        "--stdin",
+        "--stdin-filename",
+        "src/foo.rs",
+        "src/bar.rs",
+    ])
+    .is_err());
+}
+
+#[test]
+fn test_fix_interactive_args() {
+    assert!(Args::try_parse_from(["tree-sitter-lint", "--fix-interactive"]).is_ok());
+    for conflicting_arg in ["--staged", "--watch"] {
+        assert!(
+            Args::try_parse_from(["tree-sitter-lint", "--fix-interactive", conflicting_arg])
+                .is_err()
+        );
+    }
+}
+
+#[test]
+fn test_baseline_args() {
+    for baseline_arg in ["--write-baseline", "--report-stale-baseline-entries"] {
+        assert!(Args::try_parse_from([
+            "tree-sitter-lint",
+            "--baseline",
+            "baseline.json",
+            baseline_arg
+        ])
+        .is_ok());
+        // a partial run would drop or falsely report the other files' entries
+        for conflicting_args in [
+            &["src"][..],
+            &["--changed-since", "main"],
+            &["--stdin", "--stdin-filename", "whee.rs"],
+        ] {
+            assert!(Args::try_parse_from(
+                [
+                    "tree-sitter-lint",
+                    "--baseline",
+                    "baseline.json",
+                    baseline_arg
+                ]
+                .into_iter()
+                .chain(conflicting_args.iter().copied())
+            )
+            .is_err());
+        }
+    }
+}
+
+#[test]
+fn test_cascading_config_files() {
+    let top_level_config_file_path = write_config_file(
+        "cascading",
+        "root: true\nrules:\n  reports-functions:\n    level: error\n",
+    );
+    let top_level_directory = top_level_config_file_path.parent().unwrap();
+    for (subdirectory, config_file_contents) in [
+        ("", None),
+        (
+            "nested",
+            Some("rules:\n  reports-structs:\n    level: error\n"),
+        ),
+        (
+            "nested-root",
+            Some("root: true\nrules:\n  reports-structs:\n    level: error\n"),
+        ),
+    ] {
+        let directory = top_level_directory.join(subdirectory);
+        fs::create_dir_all(&directory).unwrap();
+        fs::write(directory.join("lib.rs"), "fn whee() {}\nstruct Whee;\n").unwrap();
+        if let Some(config_file_contents) = config_file_contents {
+            fs::write(
+                directory.join(".tree-sitter-lint.yml"),
+                config_file_contents,
+            )
+            .unwrap();
+        }
+    }
+    let config = ArgsBuilder::default()
+        .config(top_level_config_file_path.clone())
+        .paths(vec![top_level_directory.to_owned()])
+        .build()
+        .unwrap()
+        .load_config_file_and_into_config(
+            Default::default(),
+            vec![reports_functions_rule(), reports_structs_rule()],
+        )
+        .ok()
+        .unwrap();
+
+    let mut rule_names_by_path: BTreeMap<_, Vec<_>> = Default::default();
+    for violation in run(&config, &DummyFromFileRunContextInstanceProviderFactory)
+        .unwrap()
+        .violations
+    {
+        rule_names_by_path
+            .entry(
+                violation
+                    .path
+                    .strip_prefix(top_level_directory)
+                    .unwrap()
+                    .to_owned(),
+            )
+            .or_default()
+            .push(violation.rule.name.clone());
+    }
+    for rule_names in rule_names_by_path.values_mut() {
+        rule_names.sort();
+    }
+    assert_eq!(
+        rule_names_by_path,
+        [
+            ("lib.rs".into(), vec!["reports-functions".to_owned()]),
+            (
+                "nested/lib.rs".into(),
+                vec!["reports-functions".to_owned(), "reports-structs".to_owned()]
+            ),
+            (
+                "nested-root/lib.rs".into(),
+                vec!["reports-structs".to_owned()]
+            ),
+        ]
+        .into_iter()
+        .collect()
+    );
+
+    let nested_path = top_level_directory.join("nested/lib.rs");
+    let effective_config = config.get_effective_config_for_file(&nested_path).unwrap();
+    assert_eq!(
+        effective_config["configFile"],
+        json!(top_level_directory
+            .canonicalize()
+            .unwrap()
+            .join("nested/.tree-sitter-lint.yml"))
+    );
+    assert_eq!(
+        effective_config["rules"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .collect::<Vec<_>>(),
+        vec!["reports-functions", "reports-structs"]
+    );
+}
+
+#[test]
+fn test_extends_config_files() {
+    macro_rules! noop_rule {
+        ($name:literal) => {{
+            let rule: Arc<dyn Rule> = rule! {
+                name => $name,
+                listeners => [
+                    r#"(function_item) @c"# => |node, context| {}
+                ],
+                languages => [Rust],
+            };
+            rule
+        }};
+    }
+    let rules = vec![
+        noop_rule!("base-rule"),
+        noop_rule!("shared-rule"),
+        noop_rule!("top-level-rule"),
+    ];
+    let path = write_config_file(
+        "extends-config-files",
+        "root: true\nextends: [shared/base.yml]\nrules:\n  top-level-rule:\n    level: error\n",
+    );
+    let shared_directory = path.parent().unwrap().join("shared");
+    fs::create_dir_all(&shared_directory).unwrap();
+    fs::write(
+        shared_directory.join("base.yml"),
+        "extends: [../other/more.yml]\nrules:\n  shared-rule:\n    level: error\n",
+    )
+    .unwrap();
+    let other_directory = path.parent().unwrap().join("other");
+    fs::create_dir_all(&other_directory).unwrap();
+    fs::write(
+        other_directory.join("more.yml"),
+        "rules:\n  base-rule:\n    level: error\n  shared-rule:\n    level: off\n",
+    )
+    .unwrap();
+    let config = ArgsBuilder::default()
+        .config(path)
+        .build()
+        .unwrap()
+        .load_config_file_and_into_config(Default::default(), rules)
+        .ok()
+        .unwrap();
+
+    assert_eq!(
+        config.get_effective_config(None)["rules"],
+        json!({
+            "base-rule": {
+                "level": "error",
+                "options": null,
+                "origin": { "extends": ["shared/base.yml", "../other/more.yml"] },
+            },
+            "shared-rule": {
+                "level": "error",
+                "options": null,
+                "origin": { "extends": ["shared/base.yml"] },
+            },
+            "top-level-rule": {
+                "level": "error",
+                "options": null,
+                "origin": "configFile",
+            },
+        })
+    );
+}
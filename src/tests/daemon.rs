@@ -0,0 +1,99 @@
+#![cfg(unix)]
+
+use std::{fs, thread, time::Duration};
+
+use super::{lock_current_directory, reports_functions_rule, write_config_file};
+use crate::{
+    daemon::{can_forward_to_daemon, forward_to_daemon, get_daemon_socket_path},
+    rule_tester::DummyFromFileRunContextInstanceProviderFactory,
+    run_daemon, ArgsBuilder, Command,
+};
+
+#[test]
+fn test_daemon() {
+    // requests get run in the client's current directory
+    let _lock = lock_current_directory();
+    let config_file_path = write_config_file(
+        "daemon",
+        "root: true\nrules:\n  reports-functions:\n    level: error\n",
+    );
+    let directory = config_file_path.parent().unwrap().to_owned();
+    fs::write(directory.join("violating.rs"), "fn whee() {}\n").unwrap();
+    fs::write(directory.join("clean.rs"), "struct Whee;\n").unwrap();
+    let socket_path = get_daemon_socket_path(&config_file_path);
+
+    let args = ArgsBuilder::default()
+        .config(config_file_path)
+        .command(Command::Daemon)
+        .build()
+        .unwrap();
+    thread::spawn(move || {
+        run_daemon(
+            args,
+            |args| {
+                args.load_config_file_and_into_config(
+                    Default::default(),
+                    vec![reports_functions_rule()],
+                )
+            },
+            &DummyFromFileRunContextInstanceProviderFactory,
+        );
+    });
+    for _ in 0..100 {
+        if socket_path.exists() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+
+    let path_arg = |filename: &str| directory.join(filename).to_str().unwrap().to_owned();
+    assert_eq!(
+        forward_to_daemon(&socket_path, vec![path_arg("violating.rs")]),
+        Some(1)
+    );
+    // reuses the already-instantiated rules
+    assert_eq!(
+        forward_to_daemon(&socket_path, vec![path_arg("violating.rs")]),
+        Some(1)
+    );
+    assert_eq!(
+        forward_to_daemon(&socket_path, vec![path_arg("clean.rs")]),
+        Some(0)
+    );
+    assert_eq!(
+        forward_to_daemon(
+            &socket_path,
+            vec![
+                path_arg("clean.rs"),
+                "--rule".to_owned(),
+                "whoops".to_owned()
+            ]
+        ),
+        Some(2)
+    );
+}
+
+#[test]
+fn test_no_daemon_running() {
+    let config_file_path = write_config_file("no-daemon", "root: true\n");
+    assert_eq!(
+        forward_to_daemon(&get_daemon_socket_path(&config_file_path), vec![]),
+        None
+    );
+}
+
+#[test]
+fn test_can_forward_to_daemon() {
+    assert!(can_forward_to_daemon(
+        &ArgsBuilder::default().fix(true).build().unwrap()
+    ));
+    assert!(!can_forward_to_daemon(
+        &ArgsBuilder::default()
+            .command(Command::ListRules)
+            .build()
+            .unwrap()
+    ));
+    assert!(!can_forward_to_daemon(
+        &ArgsBuilder::default().timing(10usize).build().unwrap()
+    ));
+}
@@ -0,0 +1,92 @@
+use std::{fs, sync::Arc};
+
+use proc_macros::{rule_crate_internal as rule, rule_tests_crate_internal as rule_tests};
+
+use super::TempDirectory;
+use crate::{generate_rule_docs, rule::Rule, ConfigBuilder, Error, PluginBuilder};
+
+#[test]
+fn test_generate_rule_docs() {
+    let plugin_rule: Arc<dyn Rule> = rule! {
+        name => "no-foo",
+        fixable => true,
+        messages => [
+            no_foo => "Don't use foo",
+        ],
+        docs => {
+            description => "Disallows foo",
+            examples => rule_tests! {
+                valid => [
+                    r#"
+                        fn bar() {}
+                    "#,
+                ],
+                invalid => [
+                    {
+                        code => r#"
+                            fn foo() {}
+                        "#,
+                        output => r#"
+                            fn bar() {}
+                        "#,
+                        errors => [{ message_id => "no_foo" }],
+                    },
+                ],
+            },
+        },
+        listeners => [
+            r#"(function_item) @c"# => |node, context| {}
+        ],
+        languages => [Rust],
+    };
+    let standalone_rule: Arc<dyn Rule> = rule! {
+        name => "standalone",
+        listeners => [
+            r#"(function_item) @c"# => |node, context| {}
+        ],
+        languages => [Rust],
+    };
+    let config = ConfigBuilder::default()
+        .all_standalone_rules([standalone_rule])
+        .all_plugins([PluginBuilder::default()
+            .name("whee")
+            .rules([plugin_rule])
+            .build()
+            .unwrap()])
+        .rule_configurations([])
+        .build()
+        .unwrap();
+    let temp_directory = TempDirectory::new("docs");
+    let output_directory = temp_directory.join("rules");
+
+    let written = generate_rule_docs(&config, Some("whee"), &output_directory).unwrap();
+    assert_eq!(
+        written,
+        vec![
+            output_directory.join("no-foo.md"),
+            output_directory.join("README.md"),
+        ]
+    );
+    let rule_page = fs::read_to_string(output_directory.join("no-foo.md")).unwrap();
+    assert!(rule_page.starts_with("# no-foo\n\nDisallows foo\n"));
+    assert!(rule_page.contains("- Fixable: yes"));
+    assert!(rule_page.contains("- `no_foo`: Don't use foo"));
+    assert!(rule_page.contains(
+        "Examples of **incorrect** code:\n```\nfn foo() {}\n```\nFixed:\n```\nfn bar() {}\n```"
+    ));
+    let index = fs::read_to_string(output_directory.join("README.md")).unwrap();
+    assert!(index.contains("| [no-foo](no-foo.md) | Disallows foo | Rust | yes |"));
+    assert!(!index.contains("standalone"));
+
+    assert!(matches!(
+        generate_rule_docs(&config, Some("whoops"), &output_directory),
+        Err(Error::PluginNotFound { .. })
+    ));
+
+    let not_a_directory = temp_directory.join("not-a-directory");
+    fs::write(&not_a_directory, "").unwrap();
+    assert!(matches!(
+        generate_rule_docs(&config, Some("whee"), &not_a_directory),
+        Err(Error::DocsWrite { path, .. }) if path == not_a_directory.join("no-foo.md")
+    ));
+}
@@ -0,0 +1,317 @@
+use std::{fs, sync::Arc};
+
+use proc_macros::{rule_crate_internal as rule, violation_crate_internal as violation};
+
+use super::{
+    reports_functions_rule, run_for_slice_with_config, run_rules_for_slice, write_config_file,
+};
+use crate::{
+    config::load_config_file, rule::Rule, ArgsBuilder, ConfigBuilder, ConfigurationBuilder, Error,
+    ErrorLevel, PluginBuilder, RuleConfiguration, SourceLocation,
+};
+
+#[test]
+fn test_query_error() {
+    let rule: Arc<dyn Rule> = rule! {
+        name => "bad-query",
+        listeners => [
+            r#"(function_item"# => |node, context| {
+                context.report(violation! {
+                    node => node,
+                    message => "whee",
+                });
+            }
+        ],
+        languages => [Rust],
+    };
+    let error = run_rules_for_slice([rule], "fn whee() {}").unwrap_err();
+    let Error::Query {
+        rule_name,
+        query_text,
+        ..
+    } = &error
+    else {
+        panic!("Expected query error, got {error:?}");
+    };
+    assert_eq!(rule_name, "bad-query");
+    assert_eq!(query_text, "(function_item");
+    assert!(error.query_offset().is_some());
+    assert!(error.to_string().contains("bad-query"));
+}
+
+#[test]
+fn test_invalid_listener_selector() {
+    let rule: Arc<dyn Rule> = rule! {
+        name => "bad-selector",
+        listeners => [
+            r#"function_item:whee"# => |node, context| {
+                context.report(violation! {
+                    node => node,
+                    message => "whee",
+                });
+            }
+        ],
+        languages => [Rust],
+    };
+    let error = run_rules_for_slice([rule], "fn whee() {}").unwrap_err();
+    assert!(
+        matches!(
+            &error,
+            Error::InvalidListenerSelector { rule_name, selector, .. }
+                if rule_name == "bad-selector" && selector == "function_item:whee"
+        ),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn test_unknown_rule_argument() {
+    let config = ConfigBuilder::default()
+        .all_standalone_rules([reports_functions_rule()])
+        .default_rule_configurations()
+        .rule("reports-functionz")
+        .build()
+        .unwrap();
+    let error = config.get_instantiated_rules().err().unwrap();
+    assert!(
+        matches!(&error, Error::UnknownRule { rule_name, location: None } if rule_name == "reports-functionz"),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn test_config_file_parse_error_location() {
+    let path = write_config_file("parse-error", "plugins: {}\nrules: {}\nwhee: true\n");
+    let error = load_config_file(Some(&path)).err().unwrap();
+    assert!(matches!(&error, Error::ConfigFileParse { .. }), "{error:?}");
+    assert_eq!(error.location().map(|location| location.line), Some(3));
+}
+
+#[test]
+fn test_unknown_rule_in_config_file_location() {
+    let path = write_config_file(
+        "unknown-rule",
+        "plugins: {}\nrules:\n  reports-functionz:\n    level: error\n",
+    );
+    let error = ArgsBuilder::default()
+        .config(path.clone())
+        .build()
+        .unwrap()
+        .load_config_file_and_into_config(Default::default(), vec![reports_functions_rule()])
+        .err()
+        .unwrap();
+    assert!(
+        matches!(&error, Error::UnknownRule { rule_name, .. } if rule_name == "reports-functionz"),
+        "{error:?}"
+    );
+    assert_eq!(
+        error.location(),
+        Some(&SourceLocation {
+            path,
+            line: 3,
+            column: 3,
+        })
+    );
+}
+
+#[test]
+fn test_invalid_rule_options_in_config_file_location() {
+    let rule: Arc<dyn Rule> = rule! {
+        name => "takes-count",
+        options_type => Option<usize>,
+        listeners => [
+            r#"(function_item) @c"# => |node, context| {}
+        ],
+        languages => [Rust],
+    };
+    let path = write_config_file(
+        "invalid-rule-options",
+        "plugins: {}\nrules:\n  takes-count:\n    level: error\n    options: three\n",
+    );
+    let error = ArgsBuilder::default()
+        .config(path.clone())
+        .build()
+        .unwrap()
+        .load_config_file_and_into_config(Default::default(), vec![rule])
+        .err()
+        .unwrap();
+    assert!(
+        matches!(&error, Error::InvalidRuleOptions { rule_name, .. } if rule_name == "takes-count"),
+        "{error:?}"
+    );
+    assert_eq!(
+        error.location(),
+        Some(&SourceLocation {
+            path,
+            line: 5,
+            column: 5,
+        })
+    );
+}
+
+#[test]
+fn test_config_file_locations_are_dropped_when_uncertain() {
+    let path = write_config_file(
+        "flow-mapping-location",
+        "plugins: {}\nrules: {reports-functions: {level: error}}\n",
+    );
+    assert_eq!(
+        load_config_file(Some(&path))
+            .unwrap()
+            .find_rule_location("reports-functions"),
+        None
+    );
+
+    // same-named keys that aren't directly under `rules` don't count
+    let path = write_config_file(
+        "nested-key-location",
+        "plugins: {}\nrules:\n  takes-options:\n    level: error\n    options:\n      reports-functions: 3\n",
+    );
+    let parsed_config_file = load_config_file(Some(&path)).unwrap();
+    assert_eq!(
+        parsed_config_file.find_rule_location("reports-functions"),
+        None
+    );
+    assert_eq!(
+        parsed_config_file
+            .find_rule_options_location("takes-options", &["reports-functions".to_owned()]),
+        Some(SourceLocation {
+            path: path.clone(),
+            line: 6,
+            column: 7,
+        })
+    );
+    assert_eq!(
+        parsed_config_file.find_rule_options_location("takes-options", &["whee".to_owned()]),
+        None
+    );
+}
+
+#[test]
+fn test_invalid_rule_options_from_config_builder() {
+    let rule: Arc<dyn Rule> = rule! {
+        name => "takes-count",
+        options_type => Option<usize>,
+        listeners => [
+            r#"(function_item) @c"# => |node, context| {}
+        ],
+        languages => [Rust],
+    };
+    let error = run_for_slice_with_config(
+        "fn whee() {}",
+        &ConfigBuilder::default()
+            .all_standalone_rules([rule])
+            .rule_configurations([RuleConfiguration {
+                name: "takes-count".to_owned(),
+                level: ErrorLevel::Error,
+                options: Some("three".into()),
+            }])
+            .build()
+            .unwrap(),
+    )
+    .err()
+    .unwrap();
+    assert!(
+        matches!(&error, Error::InvalidRuleOptions { rule_name, location: None, .. } if rule_name == "takes-count"),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn test_options_for_rule_without_options_type_are_ignored() {
+    let path = write_config_file(
+        "ignored-rule-options",
+        "plugins: {}\nrules:\n  reports-functions:\n    level: error\n    options: three\n",
+    );
+    let config = ArgsBuilder::default()
+        .config(path)
+        .build()
+        .unwrap()
+        .load_config_file_and_into_config(Default::default(), vec![reports_functions_rule()])
+        .ok()
+        .unwrap();
+    let warnings = config.take_warnings();
+    assert_eq!(warnings.len(), 1);
+    assert!(
+        warnings[0].starts_with("rule 'reports-functions' doesn't take options"),
+        "{warnings:?}"
+    );
+    assert!(config.take_warnings().is_empty());
+}
+
+#[test]
+fn test_extends_config_file_cycle() {
+    let path = write_config_file("extends-cycle", "root: true\nextends: [base.yml]\n");
+    fs::write(
+        path.parent().unwrap().join("base.yml"),
+        "extends: [.tree-sitter-lint.yml]\n",
+    )
+    .unwrap();
+    let error = ArgsBuilder::default()
+        .config(path)
+        .build()
+        .unwrap()
+        .load_config_file_and_into_config(Default::default(), vec![reports_functions_rule()])
+        .err()
+        .unwrap();
+    assert!(
+        matches!(&error, Error::ExtendsCycle { chain } if chain == &["base.yml", ".tree-sitter-lint.yml"]),
+        "{error:?}"
+    );
+}
+
+#[test]
+fn test_extends_plugin_configuration_cycle() {
+    let plugin = PluginBuilder::default()
+        .name("whee")
+        .rules([reports_functions_rule()])
+        .configs([
+            (
+                "a".to_owned(),
+                ConfigurationBuilder::default()
+                    .extends(["whee/b".to_owned()])
+                    .build()
+                    .unwrap(),
+            ),
+            (
+                "b".to_owned(),
+                ConfigurationBuilder::default()
+                    .extends(["whee/a".to_owned()])
+                    .build()
+                    .unwrap(),
+            ),
+        ])
+        .build()
+        .unwrap();
+    let path = write_config_file("extends-plugin-cycle", "root: true\nextends: [whee/a]\n");
+    let error = ArgsBuilder::default()
+        .config(path)
+        .build()
+        .unwrap()
+        .load_config_file_and_into_config(vec![plugin], Default::default())
+        .err()
+        .unwrap();
+    assert_eq!(
+        error.to_string(),
+        "Cycle in extends: whee/a -> whee/b -> whee/a"
+    );
+}
+
+#[test]
+fn test_extends_unknown_plugin() {
+    let path = write_config_file(
+        "extends-unknown-plugin",
+        "root: true\nextends: [whoops/a]\n",
+    );
+    let error = ArgsBuilder::default()
+        .config(path)
+        .build()
+        .unwrap()
+        .load_config_file_and_into_config(Default::default(), vec![reports_functions_rule()])
+        .err()
+        .unwrap();
+    assert!(
+        matches!(&error, Error::UnknownPlugin { plugin_name, .. } if plugin_name == "whoops"),
+        "{error:?}"
+    );
+}
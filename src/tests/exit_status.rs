@@ -0,0 +1,137 @@
+use std::{fs, sync::Arc};
+
+use super::{replaces_foo_rule, reports_functions_rule, reports_structs_rule, write_config_file};
+use crate::{
+    filter_for_quiet, formatter::format_violations, get_exit_status, rule::Rule,
+    rule_tester::DummyFromFileRunContextInstanceProviderFactory, run, run_and_output, ArgsBuilder,
+    Command, Config, ConfigBuilder, ExitStatus, ViolationWithContext,
+};
+
+fn get_rules() -> Vec<Arc<dyn Rule>> {
+    vec![
+        reports_functions_rule(),
+        reports_structs_rule(),
+        replaces_foo_rule(),
+    ]
+}
+
+fn get_config_and_violations(
+    name: &str,
+    file_contents: &str,
+    args: &mut ArgsBuilder,
+) -> (Config, Vec<ViolationWithContext>) {
+    let config_file_path = write_config_file(
+        name,
+        "root: true\nrules:\n  reports-functions:\n    level: error\n  reports-structs:\n    \
+         level: warn\n  replace-foo:\n    level: warn\n",
+    );
+    let directory = config_file_path.parent().unwrap();
+    fs::write(directory.join("lib.rs"), file_contents).unwrap();
+    let config = args
+        .config(config_file_path.clone())
+        .paths(vec![directory.to_owned()])
+        .build()
+        .unwrap()
+        .load_config_file_and_into_config(Default::default(), get_rules())
+        .ok()
+        .unwrap();
+    let violations = run(&config, &DummyFromFileRunContextInstanceProviderFactory)
+        .unwrap()
+        .violations;
+    (config, violations)
+}
+
+#[test]
+fn test_exit_status_errors_and_warnings() {
+    let (config, violations) = get_config_and_violations(
+        "exit-status-errors",
+        "fn whee() {}\nstruct Whee;\n",
+        &mut ArgsBuilder::default(),
+    );
+    assert_eq!(violations.len(), 2);
+    assert_eq!(get_exit_status(&violations, &config).code(), 1);
+
+    let (config, violations) = get_config_and_violations(
+        "exit-status-warnings",
+        "struct Whee;\n",
+        &mut ArgsBuilder::default(),
+    );
+    assert_eq!(violations.len(), 1);
+    assert!(violations[0].is_warning());
+    assert_eq!(get_exit_status(&violations, &config).code(), 0);
+}
+
+#[test]
+fn test_max_warnings() {
+    let file_contents = "struct Whee;\nstruct Foo;\n";
+    let (config, violations) = get_config_and_violations(
+        "max-warnings-exceeded",
+        file_contents,
+        ArgsBuilder::default().max_warnings(1usize),
+    );
+    assert_eq!(get_exit_status(&violations, &config).code(), 1);
+
+    let (config, violations) = get_config_and_violations(
+        "max-warnings-not-exceeded",
+        file_contents,
+        ArgsBuilder::default().max_warnings(2usize),
+    );
+    assert_eq!(get_exit_status(&violations, &config).code(), 0);
+}
+
+#[test]
+fn test_quiet() {
+    colored::control::set_override(false);
+
+    let (config, violations) = get_config_and_violations(
+        "quiet",
+        "struct Whee;\nstruct Foo;\n",
+        ArgsBuilder::default().quiet(true).max_warnings(0usize),
+    );
+    assert_eq!(violations.len(), 2);
+    // still counts the warnings that don't get output
+    assert_eq!(get_exit_status(&violations, &config).code(), 1);
+    let violations = filter_for_quiet(violations, &config);
+    assert!(violations.is_empty());
+    assert_eq!(
+        format_violations(&violations, &config, |_| None).unwrap(),
+        ""
+    );
+}
+
+#[test]
+fn test_stylish_summary_with_warnings() {
+    colored::control::set_override(false);
+
+    let (config, violations) = get_config_and_violations(
+        "stylish-warnings",
+        "fn whee() {}\nstruct Whee;\n",
+        &mut ArgsBuilder::default(),
+    );
+    let output = format_violations(&violations, &config, |_| None).unwrap();
+    assert!(output.contains("  1:1  error    function  reports-functions\n"));
+    assert!(output.contains("  2:1  warning  struct    reports-structs\n"));
+    assert!(output.ends_with("✖ 2 problems (1 error, 1 warning)\n"));
+
+    let (config, violations) = get_config_and_violations(
+        "stylish-fixable",
+        "fn foo() {}\nstruct Whee;\n",
+        &mut ArgsBuilder::default(),
+    );
+    let output = format_violations(&violations, &config, |_| None).unwrap();
+    assert!(output.ends_with("✖ 3 problems (1 error, 2 warnings; 1 fixable with --fix)\n"));
+}
+
+#[test]
+fn test_daemon_command_from_config() {
+    let config = ConfigBuilder::default()
+        .all_standalone_rules(get_rules())
+        .default_rule_configurations()
+        .command(Command::Daemon)
+        .build()
+        .unwrap();
+    assert!(matches!(
+        run_and_output(config, &DummyFromFileRunContextInstanceProviderFactory),
+        ExitStatus::SetupError
+    ));
+}
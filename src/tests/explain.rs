@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use proc_macros::{rule_crate_internal as rule, rule_tests_crate_internal as rule_tests};
+
+use crate::{
+    explain::get_rule_list_rows, get_rule_explanation, rule::Rule, ConfigBuilder, Error,
+    ErrorLevel, PluginBuilder, RuleConfiguration,
+};
+
+fn get_config() -> crate::Config {
+    let standalone_rule: Arc<dyn Rule> = rule! {
+        name => "takes-count",
+        fixable => true,
+        messages => [
+            too_many => "Too many: {{count}}",
+        ],
+        docs => {
+            description => r#"
+                Limits things.
+
+                More details.
+            "#,
+            examples => rule_tests! {
+                valid => [
+                    r#"
+                        fn foo() {}
+                    "#,
+                ],
+                invalid => [],
+            },
+        },
+        listeners => [
+            r#"(function_item) @c"# => |node, context| {}
+        ],
+        languages => [Rust],
+    };
+    let plugin_rule: Arc<dyn Rule> = rule! {
+        name => "no-foo",
+        listeners => [
+            r#"(function_item) @c"# => |node, context| {}
+        ],
+        languages => [Rust, Javascript],
+    };
+    ConfigBuilder::default()
+        .all_standalone_rules([standalone_rule])
+        .all_plugins([PluginBuilder::default()
+            .name("whee")
+            .rules([plugin_rule])
+            .build()
+            .unwrap()])
+        .rule_configurations([RuleConfiguration {
+            name: "whee/no-foo".to_owned(),
+            level: ErrorLevel::Off,
+            options: None,
+        }])
+        .build()
+        .unwrap()
+}
+
+#[test]
+fn test_rule_list_rows() {
+    let config = get_config();
+    let rows = get_rule_list_rows(&config);
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].plugin, None);
+    assert_eq!(rows[0].name, "takes-count");
+    assert!(rows[0].fixable);
+    assert_eq!(rows[0].level, None);
+    assert_eq!(rows[0].description.as_deref(), Some("Limits things."));
+    assert_eq!(rows[1].plugin.as_deref(), Some("whee"));
+    assert_eq!(rows[1].name, "no-foo");
+    assert_eq!(rows[1].languages, "Rust, Javascript");
+    assert_eq!(rows[1].level, Some(ErrorLevel::Off));
+}
+
+#[test]
+fn test_explain() {
+    let config = get_config();
+    let explanation = get_rule_explanation(&config, "takes-count").unwrap();
+    assert!(explanation.starts_with("# takes-count\n\nLimits things.\n\nMore details."));
+    assert!(explanation.contains("- Fixable: yes"));
+    assert!(explanation.contains("- `too_many`: Too many: {{count}}"));
+    assert!(explanation.contains("Examples of **correct** code:\n```\nfn foo() {}\n```"));
+
+    assert!(matches!(
+        get_rule_explanation(&config, "no-foo"),
+        Err(Error::UnknownRule { .. })
+    ));
+}
@@ -0,0 +1,185 @@
+use std::{
+    collections::HashMap,
+    io::{self, Cursor, Write},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use super::{create_identifier_replacing_rule, run_for_slice_with_config, SLICE_PATH};
+use crate::{
+    fixing::FixReviewer, Config, ConfigBuilder, PendingFix, RuleMeta, RuleName,
+    ViolationWithContext,
+};
+
+#[derive(Clone, Default)]
+struct SharedOutput(Arc<Mutex<Vec<u8>>>);
+
+impl SharedOutput {
+    fn contents(&self) -> String {
+        String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+    }
+}
+
+impl Write for SharedOutput {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn get_config() -> Config {
+    ConfigBuilder::default()
+        .all_standalone_rules([
+            create_identifier_replacing_rule("foo", "bar"),
+            create_identifier_replacing_rule("baz", "qux"),
+        ])
+        .default_rule_configurations()
+        .build()
+        .unwrap()
+}
+
+// Outside of fixing mode violations come with their fixes attached, like they
+// do for an interactive fixing run
+#[allow(clippy::type_complexity)]
+fn get_violations_and_pending_fixes(
+    file_contents: &str,
+    config: &Config,
+) -> (
+    Vec<ViolationWithContext>,
+    HashMap<RuleName, (Vec<PendingFix>, Arc<RuleMeta>)>,
+) {
+    let mut violations = run_for_slice_with_config(file_contents, config).unwrap();
+    let mut pending_fixes: HashMap<RuleName, (Vec<PendingFix>, Arc<RuleMeta>)> = Default::default();
+    for violation in &mut violations {
+        violation.had_fixes = true;
+        pending_fixes
+            .entry(violation.rule.name.clone())
+            .or_insert_with(|| (Default::default(), violation.rule.clone()))
+            .0
+            .extend(violation.fixes.clone().unwrap());
+    }
+    (violations, pending_fixes)
+}
+
+fn get_fix_reviewer(input: &str) -> (FixReviewer, SharedOutput) {
+    let output = SharedOutput::default();
+    (
+        FixReviewer::new(
+            Box::new(Cursor::new(input.to_owned())),
+            Box::new(output.clone()),
+        ),
+        output,
+    )
+}
+
+fn get_accepted_replacements(
+    accepted_fixes: &HashMap<RuleName, (Vec<PendingFix>, Arc<RuleMeta>)>,
+) -> Vec<(usize, String)> {
+    let mut replacements = accepted_fixes
+        .values()
+        .flat_map(|(pending_fixes, _)| {
+            pending_fixes.iter().map(|pending_fix| {
+                (
+                    pending_fix.range.start_byte,
+                    pending_fix.replacement.clone(),
+                )
+            })
+        })
+        .collect::<Vec<_>>();
+    replacements.sort();
+    replacements
+}
+
+#[test]
+fn test_fix_review() {
+    colored::control::set_override(false);
+
+    let config = get_config();
+    let file_contents = "fn foo() {}\nfn baz() {\n    foo();\n}\n";
+    let (mut violations, pending_fixes) = get_violations_and_pending_fixes(file_contents, &config);
+    let (mut fix_reviewer, output) = get_fix_reviewer("a\nwhee\nn\n");
+
+    let accepted_fixes = fix_reviewer.review(
+        Path::new(SLICE_PATH),
+        file_contents.as_bytes(),
+        pending_fixes,
+        &mut violations,
+        &config,
+    );
+    // the second `foo` gets accepted without prompting
+    assert_eq!(
+        get_accepted_replacements(&accepted_fixes),
+        vec![(3, "bar".to_owned()), (27, "bar".to_owned())]
+    );
+    let violation_was_fixed = |name: &str| {
+        violations
+            .iter()
+            .find(|violation| violation.rule.name == name)
+            .unwrap()
+            .had_fixes
+    };
+    assert!(violation_was_fixed("replace_foo_with_bar"));
+    assert!(!violation_was_fixed("replace_baz_with_qux"));
+
+    let prompt = "Apply this fix? [y]es, [n]o, [a]ll for this rule, [q]uit: \n";
+    assert_eq!(
+        output.contents(),
+        format!(
+            "src/whee.rs:1:4  Use 'bar' instead of 'foo'  replace_foo_with_bar\n\
+             @@ -1,1 +1,1 @@\n\
+             -fn foo() {{}}\n\
+             +fn bar() {{}}\n\
+             {prompt}\
+             src/whee.rs:2:4  Use 'qux' instead of 'baz'  replace_baz_with_qux\n\
+             @@ -2,1 +2,1 @@\n\
+             -fn baz() {{\n\
+             +fn qux() {{\n\
+             {prompt}{prompt}"
+        )
+    );
+}
+
+#[test]
+fn test_fix_review_remembers_skipped_fixes() {
+    let config = get_config();
+    let file_contents = "fn foo() {}\n";
+    let (mut fix_reviewer, output) = get_fix_reviewer("n\n");
+
+    // as if the fixing loop had re-run the rules after another fix
+    for _ in 0..2 {
+        let (mut violations, pending_fixes) =
+            get_violations_and_pending_fixes(file_contents, &config);
+        let accepted_fixes = fix_reviewer.review(
+            Path::new(SLICE_PATH),
+            file_contents.as_bytes(),
+            pending_fixes,
+            &mut violations,
+            &config,
+        );
+        assert!(accepted_fixes.is_empty());
+        assert!(!violations[0].had_fixes);
+    }
+    assert_eq!(output.contents().matches("Apply this fix?").count(), 1);
+}
+
+#[test]
+fn test_fix_review_quit() {
+    let config = get_config();
+    let file_contents = "fn foo() {}\nfn baz() {}\n";
+    let (mut violations, pending_fixes) = get_violations_and_pending_fixes(file_contents, &config);
+    let (mut fix_reviewer, output) = get_fix_reviewer("q\n");
+
+    let accepted_fixes = fix_reviewer.review(
+        Path::new(SLICE_PATH),
+        file_contents.as_bytes(),
+        pending_fixes,
+        &mut violations,
+        &config,
+    );
+    assert!(accepted_fixes.is_empty());
+    assert!(violations.iter().all(|violation| !violation.had_fixes));
+    assert_eq!(output.contents().matches("Apply this fix?").count(), 1);
+}
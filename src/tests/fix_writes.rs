@@ -0,0 +1,84 @@
+#![cfg(unix)]
+
+use std::{fs, os::unix::fs::PermissionsExt, path::Path};
+
+use super::{replaces_foo_rule, reports_structs_rule, TempDirectory};
+use crate::{
+    fixing::write_files, rule_tester::DummyFromFileRunContextInstanceProviderFactory, run,
+    ConfigBuilder, FixWriteFailure, RunStatus,
+};
+
+fn write_file(path: &Path, file_contents: &str) -> Vec<FixWriteFailure> {
+    write_files([(path, file_contents.as_bytes())].into_iter())
+}
+
+#[test]
+fn test_fix_write_preserves_permissions() {
+    let directory = TempDirectory::new("fix-write-permissions");
+    let path = directory.join("script.rs");
+    fs::write(&path, "fn whee() {}\n").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o754)).unwrap();
+
+    assert!(write_file(&path, "fn bar() {}\n").is_empty());
+    assert_eq!(fs::read_to_string(&path).unwrap(), "fn bar() {}\n");
+    assert_eq!(
+        fs::metadata(&path).unwrap().permissions().mode() & 0o777,
+        0o754
+    );
+    // no leftover temp file
+    assert_eq!(fs::read_dir(&*directory).unwrap().count(), 1);
+}
+
+#[test]
+fn test_fix_write_failures_are_reported_per_file() {
+    let directory = TempDirectory::new("fix-write-failures");
+    let read_only_path = directory.join("read_only.rs");
+    let writable_path = directory.join("writable.rs");
+    for path in [&read_only_path, &writable_path] {
+        fs::write(path, "fn whee() {}\n").unwrap();
+    }
+    fs::set_permissions(&read_only_path, fs::Permissions::from_mode(0o444)).unwrap();
+
+    let failures = write_files(
+        [
+            (&*read_only_path, &b"fn bar() {}\n"[..]),
+            (&*writable_path, &b"fn bar() {}\n"[..]),
+        ]
+        .into_iter(),
+    );
+    assert_eq!(failures.len(), 1);
+    assert_eq!(failures[0].path, read_only_path);
+    assert_eq!(
+        fs::read_to_string(&read_only_path).unwrap(),
+        "fn whee() {}\n"
+    );
+    assert_eq!(fs::read_to_string(&writable_path).unwrap(), "fn bar() {}\n");
+}
+
+#[test]
+fn test_fix_write_failures_keep_violations() {
+    let directory = TempDirectory::new("fix-write-failures-violations");
+    let path = directory.join("read_only.rs");
+    fs::write(&path, "fn foo() {}\nstruct Whee;\n").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o444)).unwrap();
+    let config = ConfigBuilder::default()
+        .all_standalone_rules([replaces_foo_rule(), reports_structs_rule()])
+        .default_rule_configurations()
+        .fix(true)
+        .paths(vec![directory.to_owned()])
+        .build()
+        .unwrap();
+
+    let RunStatus {
+        violations,
+        fix_write_failures,
+    } = run(&config, &DummyFromFileRunContextInstanceProviderFactory).unwrap();
+    assert_eq!(fix_write_failures.len(), 1);
+    assert_eq!(
+        violations
+            .iter()
+            .map(|violation| &*violation.rule.name)
+            .collect::<Vec<_>>(),
+        vec!["reports-structs"]
+    );
+}
@@ -1,13 +1,12 @@
 #![cfg(test)]
 
-use std::sync::Arc;
-
 use proc_macros::{
     rule_crate_internal as rule, rule_tests_crate_internal as rule_tests,
     violation_crate_internal as violation,
 };
 
-use crate::{rule::Rule, RuleTester};
+use super::create_identifier_replacing_rule;
+use crate::RuleTester;
 
 #[macro_export]
 macro_rules! assert_fixed_content {
@@ -26,7 +25,8 @@ macro_rules! assert_fixed_content {
             $crate::tree_sitter_grep::SupportedLanguageLanguage::Rust,
             &$crate::rule_tester::DummyFromFileRunContextInstanceProviderFactory,
             Default::default(),
-        );
+        )
+        .unwrap();
         assert_eq!(
             std::str::from_utf8(&file_contents).unwrap().trim(),
             $output.trim()
@@ -125,35 +125,49 @@ fn test_multiple_nonconflicting_fixes_from_different_rules() {
     );
 }
 
-fn create_identifier_replacing_rule(
-    name: impl Into<String>,
-    replacement: impl Into<String>,
-) -> Arc<dyn Rule> {
-    rule! {
-        name => format!("replace_{}_with_{}", self.name, self.replacement),
-        fixable => true,
-        state => {
-            [rule-static]
-            name: String = name.into(),
-            replacement: String = replacement.into(),
-        },
-        listeners => [
-            format!(r#"(
-              (identifier) @c (#eq? @c "{}")
-            )"#, self.name) => |node, context| {
-                context.report(
-                    violation! {
-                        message => format!(r#"Use '{}' instead of '{}'"#, self.replacement, self.name),
-                        node => node,
-                        fix => |fixer| {
-                            fixer.replace_text(node, &self.replacement);
-                        },
+#[test]
+fn test_fixes_keep_crlf_line_endings() {
+    assert_fixed_content!(
+        "fn foo() {}\r\n",
+        [create_identifier_replacing_rule("foo", "bar() {}\nfn baz")],
+        "fn bar() {}\r\nfn baz() {}\r\n"
+    );
+}
+
+#[test]
+fn test_fixes_leave_mixed_line_endings_alone() {
+    assert_fixed_content!(
+        "fn foo() {}\r\nfn whee() {}\n",
+        [create_identifier_replacing_rule("foo", "bar() {}\nfn baz")],
+        "fn bar() {}\nfn baz() {}\r\nfn whee() {}\n"
+    );
+}
+
+#[test]
+fn test_fixes_can_change_crlf_line_endings() {
+    assert_fixed_content!(
+        "fn foo() {}\r\nfn bar() {}\r\n",
+        [rule! {
+            name => "no-crlf",
+            fixable => true,
+            listeners => [
+                r#"(source_file) @c"# => |node, context| {
+                    let text = context.get_node_text(node);
+                    if text.contains('\r') {
+                        context.report(violation! {
+                            node => node,
+                            message => "Use LF line endings",
+                            fix => |fixer| {
+                                fixer.replace_text(node, text.replace("\r\n", "\n"));
+                            },
+                        });
                     }
-                );
-            }
-        ],
-        languages => [Rust]
-    }
+                }
+            ],
+            languages => [Rust],
+        }],
+        "fn foo() {}\nfn bar() {}\n"
+    );
 }
 
 #[test]
@@ -0,0 +1,171 @@
+use std::{collections::HashMap, path::Path, sync::Arc};
+
+use serde_json::json;
+
+use super::{reports_functions_rule_with_message, run_for_slice_with_config};
+use crate::{
+    formatter::format_violations, rule::Rule, Config, ConfigBuilder, Error, Formatter,
+    PluginBuilder, ViolationWithContext,
+};
+
+const MESSAGE: &str = "Don't use <functions> & such";
+
+fn get_config(format: &str) -> Config {
+    ConfigBuilder::default()
+        .all_standalone_rules([reports_functions_rule_with_message(MESSAGE)])
+        .default_rule_configurations()
+        .format(format)
+        .code_frame(true)
+        .build()
+        .unwrap()
+}
+
+fn format(file_contents: &str, config: &Config) -> String {
+    let violations = run_for_slice_with_config(file_contents, config).unwrap();
+    format_violations(&violations, config, |_| {
+        Some(file_contents.as_bytes().to_owned())
+    })
+    .unwrap()
+}
+
+#[test]
+fn test_stylish_format_with_code_frames() {
+    colored::control::set_override(false);
+
+    assert_eq!(
+        format(
+            "fn whee() {}\n\nfn foo() {\n    bar();\n}\n",
+            &get_config("stylish")
+        ),
+        r#"src/whee.rs
+  1:1  error  Don't use <functions> & such  reports-functions
+    > 1 | fn whee() {}
+        | ^^^^^^^^^^^^
+      2 |
+  3:1  error  Don't use <functions> & such  reports-functions
+      2 |
+    > 3 | fn foo() {
+        | ^^^^^^^^^^
+      4 |     bar();
+
+✖ 2 problems
+"#
+    );
+}
+
+#[test]
+fn test_checkstyle_format() {
+    assert_eq!(
+        format("fn whee() {}\n", &get_config("checkstyle")),
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<checkstyle version="4.3">
+  <file name="src/whee.rs">
+    <error line="1" column="1" severity="error" message="Don&apos;t use &lt;functions&gt; &amp; such" source="reports-functions" />
+  </file>
+</checkstyle>
+"#
+    );
+}
+
+#[test]
+fn test_junit_format() {
+    assert_eq!(
+        format("fn whee() {}\n", &get_config("junit")),
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<testsuites>
+  <testsuite package="tree-sitter-lint" time="0" tests="1" errors="1" name="src/whee.rs">
+    <testcase time="0" name="reports-functions" classname="src/whee.rs">
+      <failure message="Don&apos;t use &lt;functions&gt; &amp; such"><![CDATA[line 1, col 1, Error - Don't use <functions> & such (reports-functions)]]></failure>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#
+    );
+}
+
+#[test]
+fn test_github_format() {
+    assert_eq!(
+        format("fn whee() {}\n", &get_config("github")),
+        "::error file=src/whee.rs,line=1,col=1,endLine=1,endColumn=13,title=reports-functions::\
+         Don't use <functions> & such\n"
+    );
+}
+
+#[test]
+fn test_gitlab_format() {
+    let mut issues: serde_json::Value =
+        serde_json::from_str(&format("fn whee() {}\n", &get_config("gitlab"))).unwrap();
+    let fingerprint = issues[0]
+        .as_object_mut()
+        .unwrap()
+        .remove("fingerprint")
+        .unwrap();
+    assert_eq!(fingerprint.as_str().unwrap().len(), 16);
+    assert_eq!(
+        issues,
+        json!([{
+            "description": MESSAGE,
+            "check_name": "reports-functions",
+            "severity": "major",
+            "location": {
+                "path": "src/whee.rs",
+                "lines": {
+                    "begin": 1,
+                    "end": 1,
+                },
+            },
+        }])
+    );
+}
+
+struct CountingFormatter;
+
+impl Formatter for CountingFormatter {
+    fn format(
+        &self,
+        violations: &[ViolationWithContext],
+        _config: &Config,
+        _get_file_contents: &dyn Fn(&Path) -> Option<Vec<u8>>,
+    ) -> String {
+        format!("{} violation(s)\n", violations.len())
+    }
+}
+
+#[test]
+fn test_plugin_formatter() {
+    let plugin = PluginBuilder::default()
+        .name("whee")
+        .formatters(HashMap::from([(
+            "counting".to_owned(),
+            Arc::new(CountingFormatter) as Arc<dyn Formatter>,
+        )]))
+        .build()
+        .unwrap();
+    let get_config = |format: &str| {
+        ConfigBuilder::default()
+            .all_standalone_rules(Vec::<Arc<dyn Rule>>::new())
+            .all_plugins([plugin.clone()])
+            .default_rule_configurations()
+            .format(format)
+            .build()
+            .unwrap()
+    };
+
+    assert_eq!(
+        get_config("whee/counting").get_formatter().unwrap().format(
+            &[],
+            &get_config("whee/counting"),
+            &|_| None
+        ),
+        "0 violation(s)\n"
+    );
+    assert!(matches!(
+        get_config("whee/whoops").get_formatter(),
+        Err(Error::UnknownFormatter { name }) if name == "whee/whoops"
+    ));
+    assert!(matches!(
+        get_config("whoops").get_formatter(),
+        Err(Error::UnknownFormatter { .. })
+    ));
+}
@@ -0,0 +1,160 @@
+use std::{fs, path::PathBuf, process::Command};
+
+use super::{reports_functions_rule, CurrentDirectory, TempDirectory};
+use crate::{
+    git::parse_diff, rule_tester::DummyFromFileRunContextInstanceProviderFactory, run, Config,
+    ConfigBuilder,
+};
+
+#[test]
+fn test_parse_diff() {
+    let changed_lines = parse_diff(
+        r#"diff --git src/whee.rs src/whee.rs
+index 1234567..89abcde 100644
+--- src/whee.rs
++++ src/whee.rs
+@@ -3 +3 @@ fn whee() {
+-    let x = 1;
++    let x = 2;
+@@ -10,0 +11,3 @@ fn whee() {
++fn foo() {
++    bar();
++}
+@@ -20,2 +23,0 @@ fn baz() {
+-    qux();
+-    quux();
+diff --git src/new.rs src/new.rs
+new file mode 100644
+index 0000000..1234567
+--- /dev/null
++++ src/new.rs
+@@ -0,0 +1,2 @@
++struct Whee;
++struct Foo;
+"#,
+    );
+    assert_eq!(changed_lines.len(), 2);
+    assert_eq!(
+        changed_lines[&PathBuf::from("src/whee.rs")],
+        vec![3..=3, 11..=13]
+    );
+    assert_eq!(changed_lines[&PathBuf::from("src/new.rs")], vec![1..=2]);
+}
+
+#[test]
+fn test_parse_diff_only_deletions() {
+    let changed_lines = parse_diff(
+        r#"diff --git src/whee.rs src/whee.rs
+index 1234567..89abcde 100644
+--- src/whee.rs
++++ src/whee.rs
+@@ -5,2 +4,0 @@ fn whee() {
+-    foo();
+-    bar();
+"#,
+    );
+    assert_eq!(changed_lines[&PathBuf::from("src/whee.rs")], vec![]);
+}
+
+#[test]
+fn test_parse_diff_quoted_paths() {
+    let changed_lines = parse_diff(
+        r#"diff --git "src/\"whee\".rs" "src/\"whee\".rs"
+--- "src/\"whee\".rs"
++++ "src/\"whee\".rs"
+@@ -1 +1 @@
+-struct Whee;
++struct Foo;
+diff --git "src/caf\303\251.rs" "src/caf\303\251.rs"
+--- "src/caf\303\251.rs"
++++ "src/caf\303\251.rs"
+@@ -1 +1 @@
+-struct Whee;
++struct Foo;
+diff --git src/with space.rs src/with space.rs
+--- src/with space.rs	
++++ src/with space.rs	
+@@ -1 +1 @@
+-struct Whee;
++struct Foo;
+"#,
+    );
+    for path in [r#"src/"whee".rs"#, "src/café.rs", "src/with space.rs"] {
+        assert_eq!(changed_lines[&PathBuf::from(path)], vec![1..=1], "{path}");
+    }
+}
+
+fn git(args: &[&str]) {
+    let output = Command::new("git")
+        .args(["-c", "user.name=whee", "-c", "user.email=whee@example.com"])
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}
+
+fn get_config(config_builder: ConfigBuilder) -> Config {
+    config_builder
+        .all_standalone_rules([reports_functions_rule()])
+        .default_rule_configurations()
+        .build()
+        .unwrap()
+}
+
+fn get_violation_lines(config: &Config) -> Vec<usize> {
+    let mut lines = run(config, &DummyFromFileRunContextInstanceProviderFactory)
+        .unwrap()
+        .violations
+        .into_iter()
+        .map(|violation| violation.range.start_point.row + 1)
+        .collect::<Vec<_>>();
+    lines.sort();
+    lines
+}
+
+#[test]
+fn test_staged_lints_index_contents() {
+    let directory = TempDirectory::new("git-staged");
+    let _current_directory = CurrentDirectory::set(&directory);
+    git(&["init", "-q"]);
+    // non-ASCII to make sure the path doesn't come back quoted
+    fs::write("café.rs", "struct Whee;\n").unwrap();
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "Initial"]);
+
+    fs::write("café.rs", "struct Whee;\nfn whee() {}\n").unwrap();
+    git(&["add", "."]);
+    fs::write("café.rs", "struct Whee;\n").unwrap();
+    assert_eq!(
+        get_violation_lines(&get_config(ConfigBuilder::default().staged(true))),
+        vec![2]
+    );
+}
+
+#[test]
+fn test_changed_lines_only() {
+    let directory = TempDirectory::new("git-changed-lines-only");
+    let _current_directory = CurrentDirectory::set(&directory);
+    git(&["init", "-q"]);
+    fs::write("whee.rs", "fn whee() {}\n").unwrap();
+    git(&["add", "."]);
+    git(&["commit", "-q", "-m", "Initial"]);
+
+    fs::write("whee.rs", "fn whee() {}\nfn foo() {}\n").unwrap();
+    assert_eq!(
+        get_violation_lines(&get_config(ConfigBuilder::default().changed_since("HEAD"))),
+        vec![1, 2]
+    );
+    assert_eq!(
+        get_violation_lines(&get_config(
+            ConfigBuilder::default()
+                .changed_since("HEAD")
+                .changed_lines_only(true)
+        )),
+        vec![2]
+    );
+}
@@ -1,4 +1,226 @@
 #![cfg(test)]
 
+mod baseline;
+mod cli;
+mod config;
+mod daemon;
+mod docs;
+mod errors;
+mod exit_status;
+mod explain;
+mod fix_review;
+mod fix_writes;
 mod fixing;
+mod formatter;
+mod git;
 mod rules;
+mod schema;
+mod stdin;
+mod timing;
+mod trace;
+mod watch;
+
+use std::{
+    env, fs,
+    ops::Deref,
+    path::{Path, PathBuf},
+    process,
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+};
+
+use proc_macros::{rule_crate_internal as rule, violation_crate_internal as violation};
+
+use crate::{
+    rule::Rule, rule_tester::DummyFromFileRunContextInstanceProviderFactory, run_for_slice,
+    tree_sitter_grep::SupportedLanguageLanguage, Config, ConfigBuilder, Error,
+    ViolationWithContext,
+};
+
+const SLICE_PATH: &str = "src/whee.rs";
+
+fn reports_functions_rule() -> Arc<dyn Rule> {
+    reports_functions_rule_with_message("function")
+}
+
+fn reports_functions_rule_with_message(message: &str) -> Arc<dyn Rule> {
+    rule! {
+        name => "reports-functions",
+        state => {
+            [rule-static]
+            message: String = message.to_owned(),
+        },
+        listeners => [
+            r#"(function_item) @c"# => |node, context| {
+                context.report(violation! {
+                    node => node,
+                    message => self.message.clone(),
+                });
+            }
+        ],
+        languages => [Rust],
+    }
+}
+
+fn reports_structs_rule() -> Arc<dyn Rule> {
+    rule! {
+        name => "reports-structs",
+        listeners => [
+            r#"(struct_item) @c"# => |node, context| {
+                context.report(violation! {
+                    node => node,
+                    message => "struct",
+                });
+            }
+        ],
+        languages => [Rust],
+    }
+}
+
+fn replaces_foo_rule() -> Arc<dyn Rule> {
+    rule! {
+        name => "replace-foo",
+        fixable => true,
+        listeners => [
+            r#"(
+              (identifier) @c (#eq? @c "foo")
+            )"# => |node, context| {
+                context.report(violation! {
+                    node => node,
+                    message => "Use 'bar' instead of 'foo'",
+                    fix => |fixer| {
+                        fixer.replace_text(node, "bar");
+                    },
+                });
+            }
+        ],
+        languages => [Rust],
+    }
+}
+
+fn create_identifier_replacing_rule(
+    name: impl Into<String>,
+    replacement: impl Into<String>,
+) -> Arc<dyn Rule> {
+    rule! {
+        name => format!("replace_{}_with_{}", self.name, self.replacement),
+        fixable => true,
+        state => {
+            [rule-static]
+            name: String = name.into(),
+            replacement: String = replacement.into(),
+        },
+        listeners => [
+            format!(r#"(
+              (identifier) @c (#eq? @c "{}")
+            )"#, self.name) => |node, context| {
+                context.report(
+                    violation! {
+                        message => format!(r#"Use '{}' instead of '{}'"#, self.replacement, self.name),
+                        node => node,
+                        fix => |fixer| {
+                            fixer.replace_text(node, &self.replacement);
+                        },
+                    }
+                );
+            }
+        ],
+        languages => [Rust]
+    }
+}
+
+fn run_rules_for_slice(
+    rules: impl Into<Vec<Arc<dyn Rule>>>,
+    file_contents: &str,
+) -> Result<Vec<ViolationWithContext>, Error> {
+    run_for_slice_with_config(
+        file_contents,
+        &ConfigBuilder::default()
+            .all_standalone_rules(rules)
+            .default_rule_configurations()
+            .build()
+            .unwrap(),
+    )
+}
+
+fn run_for_slice_with_config(
+    file_contents: &str,
+    config: &Config,
+) -> Result<Vec<ViolationWithContext>, Error> {
+    run_for_slice(
+        file_contents.as_bytes(),
+        None,
+        SLICE_PATH,
+        config,
+        SupportedLanguageLanguage::Rust,
+        &DummyFromFileRunContextInstanceProviderFactory,
+        None,
+    )
+    .map(|slice_run_status| slice_run_status.violations)
+}
+
+fn write_config_file(name: &str, contents: &str) -> PathBuf {
+    let directory = env::temp_dir().join(format!("tree-sitter-lint-{name}-{}", process::id()));
+    fs::create_dir_all(&directory).unwrap();
+    let path = directory.join(".tree-sitter-lint.yml");
+    fs::write(&path, contents).unwrap();
+    path
+}
+
+// Gets removed (along with its contents) when dropped
+struct TempDirectory(PathBuf);
+
+impl TempDirectory {
+    fn new(name: &str) -> Self {
+        let path = env::temp_dir().join(format!("tree-sitter-lint-{name}-{}", process::id()));
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+}
+
+impl Deref for TempDirectory {
+    type Target = Path;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl Drop for TempDirectory {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+// The current directory is shared by all of the tests, so tests that change it
+// (or that would be thrown off by it changing) have to hold this
+static CURRENT_DIRECTORY_LOCK: Mutex<()> = Mutex::new(());
+
+fn lock_current_directory() -> MutexGuard<'static, ()> {
+    CURRENT_DIRECTORY_LOCK
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+}
+
+// Changes back (even if the test panics) when dropped
+struct CurrentDirectory {
+    original: PathBuf,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl CurrentDirectory {
+    fn set(path: &Path) -> Self {
+        let lock = lock_current_directory();
+        let original = env::current_dir().unwrap();
+        env::set_current_dir(path).unwrap();
+        Self {
+            original,
+            _lock: lock,
+        }
+    }
+}
+
+impl Drop for CurrentDirectory {
+    fn drop(&mut self) {
+        let _ = env::set_current_dir(&self.original);
+    }
+}
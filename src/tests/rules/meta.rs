@@ -3,7 +3,7 @@ use proc_macros::{
     violation_crate_internal as violation,
 };
 
-use crate::RuleTester;
+use crate::{rule::Rule, RuleTester};
 
 #[test]
 fn test_concatenate_adjacent_insert_fixes() {
@@ -56,3 +56,40 @@ fn foo() {}
         },
     );
 }
+
+#[test]
+fn test_docs() {
+    let rule = rule! {
+        name => "has-docs",
+        docs => {
+            description => "Disallows functions",
+            examples => rule_tests! {
+                valid => [
+                    "use foo::bar;",
+                ],
+                invalid => [
+                    {
+                        code => "fn foo() {}",
+                        errors => 1,
+                    },
+                ]
+            },
+        },
+        listeners => [
+            r#"(
+              (function_item) @c
+            )"# => |node, context| {
+                context.report(violation! {
+                    node => node,
+                    message => "whee",
+                });
+            }
+        ],
+        languages => [Rust],
+    };
+    let meta = rule.meta();
+    let docs = meta.docs.as_ref().unwrap();
+    assert_eq!(docs.description.as_deref(), Some("Disallows functions"));
+    assert_eq!(docs.examples.correct, vec!["use foo::bar;".to_owned()]);
+    assert_eq!(docs.examples.incorrect, vec!["fn foo() {}".to_owned()]);
+}
@@ -10,6 +10,7 @@ use proc_macros::{
 mod meta;
 mod methods;
 mod options;
+mod panics;
 mod provided_types;
 mod rule_tester;
 mod state;
@@ -5,7 +5,7 @@ use proc_macros::{
 use serde::Deserialize;
 use serde_json::json;
 
-use crate::RuleTester;
+use crate::{Rule, RuleOptionsError, RuleTester};
 
 #[test]
 fn test_rule_options_optional() {
@@ -294,3 +294,59 @@ fn test_options_variable() {
         },
     );
 }
+
+#[test]
+fn test_validate_options() {
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Limits {
+        #[allow(dead_code)]
+        max: usize,
+    }
+
+    #[derive(Deserialize)]
+    struct Options {
+        #[allow(dead_code)]
+        limits: Limits,
+    }
+
+    let rule = rule! {
+        name => "validates-options",
+        options_type! => Options,
+        languages => [Rust],
+        listeners => [
+            "(function_item) @c" => |node, context| {}
+        ]
+    };
+
+    assert_eq!(
+        rule.validate_options(Some(&json!({ "limits": { "max": 3 } }))),
+        Ok(())
+    );
+    let error = rule
+        .validate_options(Some(&json!({ "limits": { "max": "three" } })))
+        .unwrap_err();
+    assert_eq!(error.path, vec!["limits".to_owned(), "max".to_owned()]);
+    let error = rule
+        .validate_options(Some(&json!({ "limits": { "maxx": 3 } })))
+        .unwrap_err();
+    assert_eq!(error.path, vec!["limits".to_owned(), "maxx".to_owned()]);
+    assert_eq!(
+        rule.validate_options(None),
+        Err(RuleOptionsError::new("This rule requires options"))
+    );
+}
+
+#[test]
+fn test_validate_options_no_options_type() {
+    let rule = rule! {
+        name => "no-options",
+        languages => [Rust],
+        listeners => [
+            "(function_item) @c" => |node, context| {}
+        ]
+    };
+
+    assert!(!rule.accepts_options());
+    assert_eq!(rule.validate_options(None), Ok(()));
+}
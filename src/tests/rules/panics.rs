@@ -0,0 +1,114 @@
+use std::sync::Arc;
+
+use proc_macros::{
+    rule_crate_internal as rule, rule_tests_crate_internal as rule_tests,
+    violation_crate_internal as violation,
+};
+
+use crate::{rule::Rule, tests::run_rules_for_slice, RuleTester};
+
+#[test]
+fn test_panicking_listener_is_reported_and_rule_disabled_for_file() {
+    let panicking_rule: Arc<dyn Rule> = rule! {
+        name => "panics-on-bar",
+        listeners => [
+            r#"(
+              (function_item
+                name: (identifier) @name
+              )
+            )"# => |node, context| {
+                if context.get_node_text(node) == "bar" {
+                    panic!("whoops");
+                }
+                context.report(violation! {
+                    node => node,
+                    message => "function",
+                });
+            }
+        ],
+        languages => [Rust],
+    };
+    let other_rule: Arc<dyn Rule> = rule! {
+        name => "reports-uses",
+        listeners => [
+            r#"(use_declaration) @c"# => |node, context| {
+                context.report(violation! {
+                    node => node,
+                    message => "use declaration",
+                });
+            }
+        ],
+        languages => [Rust],
+    };
+    let mut violations = run_rules_for_slice(
+        [panicking_rule, other_rule],
+        "fn foo() {}\nfn bar() {}\nfn baz() {}\nuse foo::bar;",
+    )
+    .unwrap();
+    violations.sort_by_key(|violation| violation.range.start_byte);
+    let messages = violations
+        .iter()
+        .map(|violation| violation.message().into_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        messages,
+        vec![
+            "function".to_owned(),
+            r#"Internal error: rule "panics-on-bar" panicked while processing `identifier` node: whoops"#
+                .to_owned(),
+            "use declaration".to_owned(),
+        ]
+    );
+    assert!(violations[1].is_internal_error);
+    assert!(!violations[0].is_internal_error);
+}
+
+#[test]
+fn test_panicking_per_file_instantiation_is_reported() {
+    let panicking_rule: Arc<dyn Rule> = rule! {
+        name => "panics-on-instantiation",
+        state => {
+            [per-file-run]
+            whee: usize = fail_instantiation(),
+        },
+        listeners => [
+            r#"(function_item) @c"# => |node, context| {
+                context.report(violation! {
+                    node => node,
+                    message => format!("{}", self.whee),
+                });
+            }
+        ],
+        languages => [Rust],
+    };
+    let violations = run_rules_for_slice([panicking_rule], "fn foo() {}\nfn bar() {}").unwrap();
+    assert_eq!(violations.len(), 1);
+    assert!(violations[0].is_internal_error);
+    assert!(violations[0].message().contains("instantiation failed"));
+}
+
+#[test]
+#[should_panic(expected = "whoops")]
+fn test_rule_tester_surfaces_panics() {
+    RuleTester::run(
+        rule! {
+            name => "always-panics",
+            listeners => [
+                r#"(function_item) @c"# => |_node, _context| {
+                    panic!("whoops");
+                }
+            ],
+            languages => [Rust],
+        },
+        rule_tests! {
+            valid => [
+                "fn foo() {}",
+            ],
+            invalid => []
+        },
+    );
+}
+
+fn fail_instantiation() -> usize {
+    panic!("instantiation failed");
+}
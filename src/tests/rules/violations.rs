@@ -1,9 +1,11 @@
+use std::sync::Arc;
+
 use proc_macros::{
     rule_crate_internal as rule, rule_tests_crate_internal as rule_tests,
     violation_crate_internal as violation,
 };
 
-use crate::{RuleTester, ViolationData};
+use crate::{rule::Rule, tests::run_rules_for_slice, RuleTag, RuleTester, ViolationData};
 
 #[test]
 fn test_rule_messages_non_interpolated() {
@@ -428,3 +430,50 @@ fn test_data_variable() {
         },
     );
 }
+
+#[test]
+fn test_related_locations_tags_and_fixes_for_slice() {
+    let file_contents = "fn whee() {}";
+    let rule: Arc<dyn Rule> = rule! {
+        name => "reports-related-locations",
+        fixable => true,
+        docs_url => "https://example.com/rules/reports-related-locations",
+        tags => [Unnecessary],
+        listeners => [
+            r#"(
+              (function_item
+                name: (identifier) @name
+              ) @c
+            )"# => |captures, context| {
+                context.report(violation! {
+                    node => captures["c"],
+                    message => "whee",
+                    related_locations => [(captures["name"], "named here").into()],
+                    fix => |fixer| {
+                        fixer.replace_text(captures["name"], "bar");
+                    },
+                });
+            }
+        ],
+        languages => [Rust],
+    };
+    let violations = run_rules_for_slice([rule], file_contents).unwrap();
+    assert_eq!(violations.len(), 1);
+    let violation = &violations[0];
+    assert_eq!(
+        violation.rule.docs_url.as_deref(),
+        Some("https://example.com/rules/reports-related-locations")
+    );
+    assert_eq!(violation.rule.tags, vec![RuleTag::Unnecessary]);
+    let related_locations = violation.related_locations.as_ref().unwrap();
+    assert_eq!(related_locations.len(), 1);
+    assert_eq!(related_locations[0].message, "named here");
+    assert_eq!(
+        &file_contents[related_locations[0].range.start_byte..related_locations[0].range.end_byte],
+        "whee"
+    );
+    let fixes = violation.fixes.as_ref().unwrap();
+    assert_eq!(fixes.len(), 1);
+    assert_eq!(fixes[0].replacement, "bar");
+    assert!(!violation.had_fixes);
+}
@@ -0,0 +1,78 @@
+use std::sync::Arc;
+
+use clap::Parser;
+use proc_macros::rule_crate_internal as rule;
+use serde_json::json;
+
+use crate::{
+    get_config_schema, rule::Rule, Args, Command, ConfigBuilder, ConfigurationBuilder,
+    PluginBuilder,
+};
+
+#[test]
+fn test_config_schema() {
+    let standalone_rule: Arc<dyn Rule> = rule! {
+        name => "takes-count",
+        options_type => Option<usize>,
+        options_schema => json!({ "type": "integer" }),
+        docs => {
+            description => "Limits things",
+        },
+        listeners => [
+            r#"(function_item) @c"# => |node, context| {}
+        ],
+        languages => [Rust],
+    };
+    let plugin_rule: Arc<dyn Rule> = rule! {
+        name => "no-foo",
+        listeners => [
+            r#"(function_item) @c"# => |node, context| {}
+        ],
+        languages => [Rust],
+    };
+    let config = ConfigBuilder::default()
+        .all_standalone_rules([standalone_rule])
+        .all_plugins([PluginBuilder::default()
+            .name("whee")
+            .rules([plugin_rule])
+            .configs([(
+                "recommended".to_owned(),
+                ConfigurationBuilder::default().build().unwrap(),
+            )])
+            .build()
+            .unwrap()])
+        .rule_configurations([])
+        .build()
+        .unwrap();
+
+    let schema = get_config_schema(&config);
+    assert_eq!(
+        schema["properties"]["extends"]["items"]["anyOf"][0]["enum"],
+        json!(["whee/recommended"])
+    );
+    assert!(schema["properties"]["plugins"]["properties"]
+        .get("whee")
+        .is_some());
+    let rules = &schema["properties"]["rules"]["properties"];
+    assert_eq!(
+        rules.as_object().unwrap().keys().collect::<Vec<_>>(),
+        vec!["takes-count", "whee/no-foo"]
+    );
+    assert_eq!(
+        rules["takes-count"]["properties"]["options"],
+        json!({ "type": "integer" })
+    );
+    assert_eq!(rules["takes-count"]["description"], json!("Limits things"));
+    assert_eq!(rules["whee/no-foo"]["properties"]["options"], json!({}));
+}
+
+#[test]
+fn test_schema_subcommand_vs_paths() {
+    let args = Args::parse_from(["tree-sitter-lint", "schema"]);
+    assert!(matches!(args.command, Some(Command::Schema)));
+    assert!(args.paths.is_empty());
+
+    let args = Args::parse_from(["tree-sitter-lint", "src", "tests"]);
+    assert!(args.command.is_none());
+    assert_eq!(args.paths.len(), 2);
+}
@@ -0,0 +1,124 @@
+use std::fs;
+
+use super::{
+    replaces_foo_rule, reports_functions_rule, reports_structs_rule, write_config_file,
+    TempDirectory,
+};
+use crate::{
+    rule_tester::DummyFromFileRunContextInstanceProviderFactory, run_and_format_for_stdin,
+    ArgsBuilder, ConfigBuilder, ExitStatus, FormattedRunStatus, FormattedStdinRunStatus,
+};
+
+#[test]
+fn test_stdin_fix_writes_fixed_source_to_stdout() {
+    let directory = TempDirectory::new("stdin-fix");
+    let stdin_filename = directory.join("whee.rs");
+    let config = ConfigBuilder::default()
+        .all_standalone_rules([replaces_foo_rule(), reports_structs_rule()])
+        .default_rule_configurations()
+        .fix(true)
+        .stdin_filename(stdin_filename.clone())
+        .build()
+        .unwrap();
+
+    let FormattedStdinRunStatus {
+        formatted_run_status:
+            FormattedRunStatus {
+                stdout,
+                stderr,
+                exit_status,
+            },
+        fixed_file_contents,
+    } = run_and_format_for_stdin(
+        &config,
+        &DummyFromFileRunContextInstanceProviderFactory,
+        &stdin_filename,
+        b"fn foo() {}\nstruct Whee;\n".to_vec(),
+    )
+    .unwrap();
+    assert_eq!(
+        fixed_file_contents.as_deref(),
+        Some(&b"fn bar() {}\nstruct Whee;\n"[..])
+    );
+    // stdout is reserved for the fixed source
+    assert_eq!(stdout, "");
+    assert!(stderr.contains("reports-structs"), "{stderr}");
+    assert!(!stderr.contains("replace-foo"), "{stderr}");
+    assert!(matches!(exit_status, ExitStatus::FoundViolations));
+    // nothing gets written to disk
+    assert!(!stdin_filename.exists());
+}
+
+#[test]
+fn test_stdin_uses_cascaded_config() {
+    let top_level_config_file_path = write_config_file(
+        "stdin-cascading",
+        "root: true\nrules:\n  reports-functions:\n    level: error\n",
+    );
+    let nested_directory = top_level_config_file_path.parent().unwrap().join("nested");
+    fs::create_dir_all(&nested_directory).unwrap();
+    fs::write(
+        nested_directory.join(".tree-sitter-lint.yml"),
+        "root: true\nrules:\n  reports-structs:\n    level: error\n",
+    )
+    .unwrap();
+    // doesn't have to exist
+    let stdin_filename = nested_directory.join("whee.rs");
+    let config = ArgsBuilder::default()
+        .config(top_level_config_file_path)
+        .stdin(true)
+        .stdin_filename(stdin_filename.clone())
+        .build()
+        .unwrap()
+        .load_config_file_and_into_config(
+            Default::default(),
+            vec![reports_functions_rule(), reports_structs_rule()],
+        )
+        .unwrap();
+
+    let FormattedStdinRunStatus {
+        formatted_run_status: FormattedRunStatus { stdout, .. },
+        fixed_file_contents,
+    } = run_and_format_for_stdin(
+        &config,
+        &DummyFromFileRunContextInstanceProviderFactory,
+        &stdin_filename,
+        b"fn whee() {}\nstruct Whee;\n".to_vec(),
+    )
+    .unwrap();
+    assert!(stdout.contains("reports-structs"), "{stdout}");
+    assert!(!stdout.contains("reports-functions"), "{stdout}");
+    assert_eq!(fixed_file_contents, None);
+}
+
+#[test]
+fn test_stdin_passes_through_unsupported_languages() {
+    let directory = TempDirectory::new("stdin-unsupported-language");
+    let stdin_filename = directory.join("whee.txt");
+    let config = ConfigBuilder::default()
+        .all_standalone_rules([replaces_foo_rule()])
+        .default_rule_configurations()
+        .fix(true)
+        .stdin_filename(stdin_filename.clone())
+        .build()
+        .unwrap();
+
+    let FormattedStdinRunStatus {
+        formatted_run_status:
+            FormattedRunStatus {
+                stdout,
+                exit_status,
+                ..
+            },
+        fixed_file_contents,
+    } = run_and_format_for_stdin(
+        &config,
+        &DummyFromFileRunContextInstanceProviderFactory,
+        &stdin_filename,
+        b"fn foo() {}\n".to_vec(),
+    )
+    .unwrap();
+    assert_eq!(fixed_file_contents.as_deref(), Some(&b"fn foo() {}\n"[..]));
+    assert_eq!(stdout, "");
+    assert!(matches!(exit_status, ExitStatus::Ok));
+}
@@ -0,0 +1,84 @@
+use std::{fs, sync::Arc};
+
+use proc_macros::{rule_crate_internal as rule, violation_crate_internal as violation};
+
+use super::{reports_functions_rule, run_for_slice_with_config, TempDirectory};
+use crate::{rule::Rule, ConfigBuilder, Error};
+
+#[test]
+fn test_timing_records_rules_and_listeners() {
+    let rule: Arc<dyn Rule> = rule! {
+        name => "reports-functions",
+        listeners => [
+            r#"(
+              (function_item) @c
+            )"# => |node, context| {
+                context.report(violation! {
+                    node => node,
+                    message => "whee",
+                });
+            },
+            r#"(
+              (use_declaration) @c
+            )"# => |node, context| {
+                context.report(violation! {
+                    node => node,
+                    message => "whee",
+                });
+            },
+        ],
+        languages => [Rust],
+    };
+    let config = ConfigBuilder::default()
+        .all_standalone_rules([rule])
+        .default_rule_configurations()
+        .timing(10_usize)
+        .build()
+        .unwrap();
+    run_for_slice_with_config("use foo::bar; fn whee() {}", &config).unwrap();
+    let per_rule = config.timings.per_rule(&config);
+    assert_eq!(per_rule.len(), 1);
+    assert_eq!(per_rule[0].0, "reports-functions");
+    let mut listener_indices = config
+        .timings
+        .per_listener(&config)
+        .into_iter()
+        .map(|((rule_name, listener_index), _)| {
+            assert_eq!(rule_name, "reports-functions");
+            listener_index
+        })
+        .collect::<Vec<_>>();
+    listener_indices.sort();
+    assert_eq!(listener_indices, vec![0, 1]);
+    let json = config.timings.to_json(&config);
+    assert_eq!(json["rules"][0]["rule"], "reports-functions");
+    assert_eq!(json["listeners"].as_array().unwrap().len(), 2);
+
+    let directory = TempDirectory::new("timing-json");
+    let timing_json_path = directory.join("timing.json");
+    config
+        .timings
+        .write_json(&config, &timing_json_path)
+        .ok()
+        .unwrap();
+    assert!(fs::read_to_string(&timing_json_path)
+        .unwrap()
+        .contains("reports-functions"));
+    assert!(matches!(
+        config
+            .timings
+            .write_json(&config, &directory.join("nonexistent").join("timing.json")),
+        Err(Error::OutputFileWrite { .. })
+    ));
+}
+
+#[test]
+fn test_timing_disabled_records_nothing() {
+    let config = ConfigBuilder::default()
+        .all_standalone_rules([reports_functions_rule()])
+        .default_rule_configurations()
+        .build()
+        .unwrap();
+    run_for_slice_with_config("fn whee() {}", &config).unwrap();
+    assert!(config.timings.per_rule(&config).is_empty());
+}
@@ -0,0 +1,65 @@
+use std::fs;
+
+use tracing_subscriber::prelude::*;
+
+use super::TempDirectory;
+use crate::{
+    trace::{get_cli_chrome_layer, parse_log_env_filter},
+    Error,
+};
+
+#[test]
+fn test_log_env_filter_defaults() {
+    assert_eq!(parse_log_env_filter(None, "error").to_string(), "error");
+}
+
+#[test]
+fn test_log_env_filter_directives() {
+    assert_eq!(
+        parse_log_env_filter(Some("tree_sitter_lint=debug"), "error").to_string(),
+        "tree_sitter_lint=debug"
+    );
+}
+
+#[test]
+fn test_log_env_filter_invalid_directives() {
+    assert_eq!(
+        parse_log_env_filter(Some("tree_sitter_lint=whee"), "error").to_string(),
+        "error"
+    );
+}
+
+#[test]
+fn test_trace_file() {
+    let directory = TempDirectory::new("trace-file");
+    let trace_directory = directory.join("traces");
+
+    let (chrome_layer, guard) = get_cli_chrome_layer(&trace_directory).unwrap();
+    tracing::subscriber::with_default(tracing_subscriber::registry().with(chrome_layer), || {
+        tracing::info_span!("whee").in_scope(|| {});
+    });
+    drop(guard);
+
+    let trace_file_paths = fs::read_dir(&trace_directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    assert_eq!(trace_file_paths.len(), 1);
+    let file_name = trace_file_paths[0].file_name().unwrap().to_str().unwrap();
+    assert!(file_name.starts_with("trace-cli-") && file_name.ends_with(".json"));
+    assert!(fs::read_to_string(&trace_file_paths[0])
+        .unwrap()
+        .contains("whee"));
+}
+
+#[test]
+fn test_trace_directory_not_creatable() {
+    let directory = TempDirectory::new("trace-directory-not-creatable");
+    let file_path = directory.join("whee");
+    fs::write(&file_path, "").unwrap();
+
+    assert!(matches!(
+        get_cli_chrome_layer::<tracing_subscriber::Registry>(&file_path.join("traces")),
+        Err(Error::TraceFileCreate { .. })
+    ));
+}
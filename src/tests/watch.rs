@@ -0,0 +1,100 @@
+use std::fs;
+
+use super::{reports_functions_rule, TempDirectory};
+use crate::{
+    config::CONFIG_FILENAME,
+    rule_tester::DummyFromFileRunContextInstanceProviderFactory,
+    tree_sitter::{InputEdit, Point},
+    watch::{get_input_edit, WatchState},
+    ArgsBuilder,
+};
+
+#[test]
+fn test_get_input_edit() {
+    assert_eq!(
+        get_input_edit(
+            b"fn whee() {}\nfn foo() {}\n",
+            b"fn whee() {}\nfn barfoo() {}\n"
+        ),
+        InputEdit {
+            start_byte: 16,
+            old_end_byte: 16,
+            new_end_byte: 19,
+            start_position: Point { row: 1, column: 3 },
+            old_end_position: Point { row: 1, column: 3 },
+            new_end_position: Point { row: 1, column: 6 },
+        }
+    );
+}
+
+#[test]
+fn test_get_input_edit_deleted_lines() {
+    assert_eq!(
+        get_input_edit(b"fn whee() {\n    foo();\n}\n", b"fn whee() {\n}\n"),
+        InputEdit {
+            start_byte: 12,
+            old_end_byte: 23,
+            new_end_byte: 12,
+            start_position: Point { row: 1, column: 0 },
+            old_end_position: Point { row: 2, column: 0 },
+            new_end_position: Point { row: 1, column: 0 },
+        }
+    );
+}
+
+#[test]
+fn test_update() {
+    let directory = TempDirectory::new("watch-update");
+    fs::write(
+        directory.join(CONFIG_FILENAME),
+        "root: true\nplugins: {}\nrules:\n  reports-functions:\n    level: error\n",
+    )
+    .unwrap();
+    fs::write(directory.join(".ignore"), "ignored.rs\n").unwrap();
+    let canonical_directory = directory.canonicalize().unwrap();
+    let edited_path = canonical_directory.join("edited.rs");
+    let deleted_path = canonical_directory.join("deleted.rs");
+    let created_path = canonical_directory.join("created.rs");
+    let ignored_path = canonical_directory.join("ignored.rs");
+    fs::write(&edited_path, "fn whee() {}\n").unwrap();
+    fs::write(&deleted_path, "fn whee() {}\n").unwrap();
+
+    let mut state = WatchState::load(
+        &ArgsBuilder::default()
+            .config(directory.join(CONFIG_FILENAME))
+            .paths(vec![directory.to_owned()])
+            .build()
+            .unwrap(),
+        &|args| {
+            args.load_config_file_and_into_config(
+                Default::default(),
+                vec![reports_functions_rule()],
+            )
+        },
+        &DummyFromFileRunContextInstanceProviderFactory,
+    )
+    .unwrap();
+    assert_eq!(state.get_violations(&edited_path).unwrap().len(), 1);
+    assert_eq!(state.get_violations(&deleted_path).unwrap().len(), 1);
+
+    fs::write(&edited_path, "fn whee() {}\nfn foo() {}\n").unwrap();
+    fs::remove_file(&deleted_path).unwrap();
+    fs::write(&created_path, "fn whee() {}\n").unwrap();
+    fs::write(&ignored_path, "fn whee() {}\n").unwrap();
+    state
+        .update(
+            [
+                edited_path.clone(),
+                deleted_path.clone(),
+                created_path.clone(),
+                ignored_path.clone(),
+            ]
+            .into(),
+            &DummyFromFileRunContextInstanceProviderFactory,
+        )
+        .unwrap();
+    assert_eq!(state.get_violations(&edited_path).unwrap().len(), 2);
+    assert!(state.get_violations(&deleted_path).is_none());
+    assert_eq!(state.get_violations(&created_path).unwrap().len(), 1);
+    assert!(state.get_violations(&ignored_path).is_none());
+}
@@ -0,0 +1,192 @@
+use std::{
+    collections::HashMap,
+    env, fs,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use dashmap::DashMap;
+use serde_json::json;
+
+use crate::{config::PluginIndex, rule::InstantiatedRule, Config, Error, RuleName};
+
+pub const TIMING_ENV_VAR_NAME: &str = "TIMING";
+
+const DEFAULT_TIMING_LIMIT: usize = 10;
+
+pub fn get_timing_limit_from_env() -> Option<usize> {
+    let value = env::var(TIMING_ENV_VAR_NAME).ok()?;
+    match &*value {
+        "" => None,
+        "all" => Some(usize::MAX),
+        value => Some(value.parse().unwrap_or(DEFAULT_TIMING_LIMIT)),
+    }
+}
+
+// (plugin index, rule index), resolved to the plugin-prefixed rule name only
+// when reporting so that timing each callback doesn't allocate
+type RuleKey = (Option<PluginIndex>, usize);
+
+#[derive(Debug, Default)]
+pub struct Timings {
+    per_listener: DashMap<(RuleKey, usize), Duration>,
+    per_rule_instantiation: DashMap<RuleKey, Duration>,
+}
+
+impl Timings {
+    pub(crate) fn time<TReturn>(
+        config: &Config,
+        instantiated_rule: &InstantiatedRule,
+        listener_index: Option<usize>,
+        callback: impl FnOnce() -> TReturn,
+    ) -> TReturn {
+        if config.timing.is_none() && config.timing_json_path.is_none() {
+            return callback();
+        }
+        let start = Instant::now();
+        let ret = callback();
+        let elapsed = start.elapsed();
+        let rule_key = (instantiated_rule.plugin_index, instantiated_rule.rule_index);
+        match listener_index {
+            Some(listener_index) => {
+                *config
+                    .timings
+                    .per_listener
+                    .entry((rule_key, listener_index))
+                    .or_default() += elapsed;
+            }
+            None => {
+                *config
+                    .timings
+                    .per_rule_instantiation
+                    .entry(rule_key)
+                    .or_default() += elapsed;
+            }
+        }
+        ret
+    }
+
+    pub fn per_rule(&self, config: &Config) -> Vec<(RuleName, Duration)> {
+        let mut totals: HashMap<RuleKey, Duration> = Default::default();
+        for entry in &self.per_listener {
+            *totals.entry(entry.key().0).or_default() += *entry.value();
+        }
+        for entry in &self.per_rule_instantiation {
+            *totals.entry(*entry.key()).or_default() += *entry.value();
+        }
+        sorted_by_duration(
+            totals
+                .into_iter()
+                .map(|((plugin_index, rule_index), duration)| {
+                    (
+                        config.get_plugin_prefixed_rule_name(plugin_index, rule_index),
+                        duration,
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    pub fn per_listener(&self, config: &Config) -> Vec<((RuleName, usize), Duration)> {
+        sorted_by_duration(
+            self.per_listener
+                .iter()
+                .map(|entry| {
+                    let (((plugin_index, rule_index), listener_index), duration) =
+                        (*entry.key(), *entry.value());
+                    (
+                        (
+                            config.get_plugin_prefixed_rule_name(plugin_index, rule_index),
+                            listener_index,
+                        ),
+                        duration,
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    // Goes to stderr so as not to get mixed into the formatted violations
+    pub fn print(&self, config: &Config, limit: usize) {
+        let per_rule = self.per_rule(config);
+        let total = per_rule
+            .iter()
+            .map(|(_, duration)| *duration)
+            .sum::<Duration>();
+        print_table(
+            "Rule",
+            per_rule
+                .iter()
+                .take(limit)
+                .map(|(rule_name, duration)| (rule_name.clone(), *duration)),
+            total,
+        );
+        eprintln!();
+        print_table(
+            "Listener",
+            self.per_listener(config).into_iter().take(limit).map(
+                |((rule_name, listener_index), duration)| {
+                    (format!("{rule_name}[{listener_index}]"), duration)
+                },
+            ),
+            total,
+        );
+    }
+
+    pub fn to_json(&self, config: &Config) -> serde_json::Value {
+        json!({
+            "rules": self.per_rule(config).into_iter().map(|(rule_name, duration)| {
+                json!({
+                    "rule": rule_name,
+                    "timeMs": duration.as_secs_f64() * 1000.0,
+                })
+            }).collect::<Vec<_>>(),
+            "listeners": self.per_listener(config).into_iter().map(|((rule_name, listener_index), duration)| {
+                json!({
+                    "rule": rule_name,
+                    "listenerIndex": listener_index,
+                    "timeMs": duration.as_secs_f64() * 1000.0,
+                })
+            }).collect::<Vec<_>>(),
+        })
+    }
+
+    pub fn write_json(&self, config: &Config, path: &Path) -> Result<(), Error> {
+        fs::write(
+            path,
+            serde_json::to_string_pretty(&self.to_json(config)).unwrap(),
+        )
+        .map_err(|source| Error::OutputFileWrite {
+            path: path.to_owned(),
+            source,
+        })
+    }
+}
+
+fn sorted_by_duration<TKey>(mut entries: Vec<(TKey, Duration)>) -> Vec<(TKey, Duration)> {
+    entries.sort_by(|(_, a), (_, b)| b.cmp(a));
+    entries
+}
+
+fn print_table(label: &str, rows: impl Iterator<Item = (String, Duration)>, total: Duration) {
+    let rows = rows.collect::<Vec<_>>();
+    let name_width = rows
+        .iter()
+        .map(|(name, _)| name.len())
+        .chain([label.len()])
+        .max()
+        .unwrap();
+    eprintln!("{label:name_width$} | Time (ms) | Relative");
+    eprintln!(":{}|----------:|--------:", "-".repeat(name_width));
+    for (name, duration) in rows {
+        eprintln!(
+            "{name:name_width$} | {:>9.3} | {:>7.1}%",
+            duration.as_secs_f64() * 1000.0,
+            if total.is_zero() {
+                0.0
+            } else {
+                duration.as_secs_f64() * 100.0 / total.as_secs_f64()
+            }
+        );
+    }
+}
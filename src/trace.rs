@@ -0,0 +1,145 @@
+use std::{
+    env,
+    fs::{self, File, OpenOptions},
+    io,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Sender},
+    thread,
+    time::UNIX_EPOCH,
+};
+
+use squalid::NonEmpty;
+use tracing::{error, Subscriber};
+use tracing_chrome::{ChromeLayer, ChromeLayerBuilder, FlushGuard};
+use tracing_subscriber::{prelude::*, registry::LookupSpan, reload, EnvFilter, Layer, Registry};
+
+use crate::Error;
+
+pub const LOG_ENV_VAR_NAME: &str = "TREE_SITTER_LINT_LOG";
+
+const LSP_LOG_PATH_ENV_VAR_NAME: &str = "TRACING_LOG_PATH";
+
+fn get_log_env_filter(default_directives: &str) -> EnvFilter {
+    parse_log_env_filter(
+        env::var(LOG_ENV_VAR_NAME).ok().as_deref(),
+        default_directives,
+    )
+}
+
+// Invalid directives fall back to the defaults too
+pub(crate) fn parse_log_env_filter(
+    directives: Option<&str>,
+    default_directives: &str,
+) -> EnvFilter {
+    directives
+        .and_then(|directives| EnvFilter::try_new(directives).ok())
+        .unwrap_or_else(|| EnvFilter::new(default_directives))
+}
+
+pub fn get_trace_file_path(trace_directory: &Path, trace_name: &str) -> PathBuf {
+    trace_directory.join(format!(
+        "trace-{trace_name}-{}.json",
+        UNIX_EPOCH.elapsed().unwrap().as_micros()
+    ))
+}
+
+fn create_trace_file(trace_file_path: &Path) -> Result<File, Error> {
+    trace_file_path
+        .parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| File::create(trace_file_path))
+        .map_err(|source| Error::TraceFileCreate {
+            path: trace_file_path.to_owned(),
+            source,
+        })
+}
+
+pub(crate) fn get_cli_chrome_layer<S>(
+    trace_directory: &Path,
+) -> Result<(impl Layer<S>, FlushGuard), Error>
+where
+    S: Subscriber + for<'span> LookupSpan<'span> + Send + Sync,
+{
+    let trace_file = create_trace_file(&get_trace_file_path(trace_directory, "cli"))?;
+    let (chrome_layer, guard) = ChromeLayerBuilder::new()
+        .include_args(true)
+        .writer(trace_file)
+        .build();
+    Ok((chrome_layer.with_filter(get_log_env_filter("trace")), guard))
+}
+
+// Leaves alone any subscriber that's already been set up (eg by a binary
+// that embeds this)
+pub fn init_cli_tracing(trace_directory: Option<&Path>) -> Result<Option<FlushGuard>, Error> {
+    Ok(match trace_directory {
+        Some(trace_directory) => {
+            let (chrome_layer, guard) = get_cli_chrome_layer(trace_directory)?;
+            tracing_subscriber::registry()
+                .with(chrome_layer)
+                .try_init()
+                .ok()
+                .map(|_| guard)
+        }
+        None => {
+            let _ = tracing_subscriber::fmt()
+                .with_env_filter(get_log_env_filter("error"))
+                .with_writer(io::stderr)
+                .try_init();
+            None
+        }
+    })
+}
+
+// The language server doesn't know where (or whether) to write traces until
+// it gets initialized, so the Chrome layer only gets swapped in once the first
+// trace file gets started
+pub fn init_lsp_tracing() -> Sender<PathBuf> {
+    let (chrome_layer, chrome_layer_reload_handle) =
+        reload::Layer::new(None::<ChromeLayer<Registry>>);
+    let log_layer = env::var(LSP_LOG_PATH_ENV_VAR_NAME)
+        .ok()
+        .non_empty()
+        .map(|log_file_path| {
+            let out_log = OpenOptions::new()
+                .append(true)
+                .create(true)
+                .open(log_file_path)
+                .expect("Couldn't open log file");
+            tracing_subscriber::fmt::layer()
+                .with_writer(out_log)
+                .with_filter(get_log_env_filter("error"))
+        });
+    let _ = tracing_subscriber::registry()
+        .with(chrome_layer.with_filter(get_log_env_filter("trace")))
+        .with(log_layer)
+        .try_init();
+
+    // FlushGuard isn't Sync so can't just pass it
+    // around directly I guess (for being able to end/start
+    // new traces)
+    let (sender, receiver) = mpsc::channel::<PathBuf>();
+    thread::spawn(move || {
+        let mut guard: Option<FlushGuard> = None;
+        for trace_file_path in receiver {
+            let trace_file = match create_trace_file(&trace_file_path) {
+                Ok(trace_file) => trace_file,
+                Err(error) => {
+                    error!(%error, "couldn't start new trace");
+                    continue;
+                }
+            };
+            match guard.as_ref() {
+                Some(guard) => guard.start_new(Some(Box::new(trace_file))),
+                None => {
+                    let (chrome_layer, new_guard) = ChromeLayerBuilder::new()
+                        .include_args(true)
+                        .writer(trace_file)
+                        .build();
+                    let _ = chrome_layer_reload_handle.reload(Some(chrome_layer));
+                    guard = Some(new_guard);
+                }
+            }
+        }
+    });
+    sender
+}
@@ -1,14 +1,16 @@
-use std::{borrow::Cow, collections::HashMap, fmt, path::PathBuf, rc::Rc, sync::Arc};
+use std::{any::Any, borrow::Cow, collections::HashMap, fmt, path::PathBuf, rc::Rc, sync::Arc};
 
 use derive_builder::Builder;
+use tracing::error;
 use tree_sitter_grep::tree_sitter::Range;
 
 use crate::{
-    config::PluginIndex,
+    baseline::get_fingerprint,
+    config::{ErrorLevel, PluginIndex},
     context::QueryMatchContext,
-    rule::RuleMeta,
+    rule::{InstantiatedRule, RuleMeta},
     tree_sitter::{self, Node},
-    Config, Fixer,
+    Config, FileRunContext, Fixer, NodeExt, PendingFix,
 };
 
 #[derive(Builder)]
@@ -23,6 +25,8 @@ pub struct Violation<'a> {
     pub data: Option<ViolationData>,
     #[builder(default)]
     pub range: Option<Range>,
+    #[builder(default)]
+    pub related_locations: Option<Vec<RelatedLocation>>,
 }
 
 impl<'a> fmt::Debug for Violation<'a> {
@@ -33,6 +37,7 @@ impl<'a> fmt::Debug for Violation<'a> {
             .field("has_fix", &self.fix.is_some())
             .field("data", &self.data)
             .field("range", &self.range)
+            .field("related_locations", &self.related_locations)
             .finish()
     }
 }
@@ -42,12 +47,15 @@ impl<'a> Violation<'a> {
         self,
         query_match_context: &QueryMatchContext,
         had_fixes: bool,
+        fixes: Option<Vec<PendingFix>>,
     ) -> ViolationWithContext {
+        let has_fix = self.fix.is_some();
         let Violation {
             message_or_message_id,
             node,
             data,
             range,
+            related_locations,
             ..
         } = self;
         ViolationWithContext {
@@ -58,7 +66,13 @@ impl<'a> Violation<'a> {
             rule: query_match_context.rule.meta.clone(),
             plugin_index: query_match_context.rule.plugin_index,
             had_fixes,
+            has_fix,
             data,
+            related_locations,
+            fixes,
+            is_internal_error: false,
+            level: query_match_context.rule.level,
+            fingerprint: get_fingerprint(&query_match_context.get_node_text(node)),
         }
     }
 }
@@ -90,6 +104,27 @@ pub enum MessageOrMessageId {
 
 pub type ViolationData = HashMap<String, String>;
 
+#[derive(Clone, Debug)]
+pub struct RelatedLocation {
+    pub range: Range,
+    pub message: String,
+}
+
+impl RelatedLocation {
+    pub fn new(range: Range, message: impl Into<String>) -> Self {
+        Self {
+            range,
+            message: message.into(),
+        }
+    }
+}
+
+impl<'a> From<(Node<'a>, &str)> for RelatedLocation {
+    fn from((node, message): (Node<'a>, &str)) -> Self {
+        Self::new(node.range(), message)
+    }
+}
+
 #[derive(Clone, Debug)]
 pub struct ViolationWithContext {
     pub message_or_message_id: MessageOrMessageId,
@@ -98,27 +133,86 @@ pub struct ViolationWithContext {
     pub rule: Arc<RuleMeta>,
     pub plugin_index: Option<PluginIndex>,
     pub had_fixes: bool,
+    // whether the rule offered a fix, regardless of whether it got applied
+    pub has_fix: bool,
     pub kind: &'static str,
     pub data: Option<ViolationData>,
+    pub related_locations: Option<Vec<RelatedLocation>>,
+    pub fixes: Option<Vec<PendingFix>>,
+    pub is_internal_error: bool,
+    pub level: ErrorLevel,
+    // of the reported node's text, see `get_fingerprint()`
+    pub fingerprint: u64,
 }
 
 impl ViolationWithContext {
-    pub fn print(&self, config: &Config) {
-        println!(
+    pub(crate) fn internal_error(
+        file_run_context: FileRunContext,
+        instantiated_rule: &InstantiatedRule,
+        node: Node,
+        panic_payload: &(dyn Any + Send),
+    ) -> Self {
+        let panic_message = panic_payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| {
+                panic_payload
+                    .downcast_ref::<String>()
+                    .map(|message| &**message)
+            })
+            .unwrap_or("<non-string panic payload>");
+        error!(
+            rule_name = instantiated_rule.meta.name,
+            path = ?file_run_context.path,
+            panic_message,
+            "rule panicked, disabling it for the rest of this file"
+        );
+        Self {
+            message_or_message_id: MessageOrMessageId::Message(format!(
+                "Internal error: rule {:?} panicked while processing `{}` node: {panic_message}",
+                instantiated_rule.plugin_prefixed_name(file_run_context.config),
+                node.kind(),
+            )),
+            range: node.range(),
+            path: file_run_context.path.to_owned(),
+            rule: instantiated_rule.meta.clone(),
+            plugin_index: instantiated_rule.plugin_index,
+            had_fixes: false,
+            has_fix: false,
+            kind: node.kind(),
+            data: None,
+            related_locations: None,
+            fixes: None,
+            is_internal_error: true,
+            level: ErrorLevel::Error,
+            fingerprint: get_fingerprint(&node.text(&file_run_context)),
+        }
+    }
+
+    pub fn format(&self, config: &Config) -> String {
+        format!(
             "{:?}:{}:{} {} {}",
             self.path,
             self.range.start_point.row + 1,
             self.range.start_point.column + 1,
             self.message(),
-            match self.plugin_index {
-                None => self.rule.name.clone(),
-                Some(plugin_index) => format!(
-                    "{}/{}",
-                    config.get_plugin_name(plugin_index),
-                    self.rule.name
-                ),
-            }
-        );
+            self.plugin_prefixed_rule_name(config)
+        )
+    }
+
+    pub fn is_warning(&self) -> bool {
+        self.level == ErrorLevel::Warn
+    }
+
+    pub fn plugin_prefixed_rule_name(&self, config: &Config) -> String {
+        match self.plugin_index {
+            None => self.rule.name.clone(),
+            Some(plugin_index) => format!(
+                "{}/{}",
+                config.get_plugin_name(plugin_index),
+                self.rule.name
+            ),
+        }
     }
 
     pub fn message(&self) -> Cow<'_, str> {
@@ -0,0 +1,465 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc, Mutex},
+    time::Duration,
+};
+
+use ignore::WalkBuilder;
+use notify::{RecursiveMode, Watcher};
+use tracing::{debug, instrument};
+use tree_sitter_grep::{
+    get_parser,
+    tree_sitter::{InputEdit, Point, Tree},
+    Parseable, RopeOrSlice, SupportedLanguageLanguage,
+};
+
+use crate::{
+    baseline::apply_baseline,
+    config::{canonicalize_config_file_path, find_config_file, load_config_file, CONFIG_FILENAME},
+    filter_for_quiet,
+    formatter::format_violations,
+    get_per_config_context, get_supported_language_language_for_path, get_tree_sitter_grep_args,
+    print_error, run_for_slice, Args, Config, Error, ExitStatus,
+    FromFileRunContextInstanceProviderFactory, PerConfigContext, ViolationWithContext,
+};
+
+// eg an editor saving a file tends to produce a burst of events
+const DEBOUNCE_INTERVAL: Duration = Duration::from_millis(100);
+
+const CLEAR_SCREEN: &str = "\x1b[2J\x1b[H";
+
+struct WatchedFile {
+    // as opposed to the (canonicalized) key, this is what violations get
+    // reported against
+    path: PathBuf,
+    contents: Vec<u8>,
+    tree: Tree,
+    supported_language_language: SupportedLanguageLanguage,
+    violations: Vec<ViolationWithContext>,
+}
+
+impl WatchedFile {
+    fn update_contents(&mut self, contents: Vec<u8>) {
+        self.tree.edit(&get_input_edit(&self.contents, &contents));
+        self.tree = RopeOrSlice::from(&*contents)
+            .parse(
+                &mut get_parser(self.supported_language_language.language()),
+                Some(&self.tree),
+            )
+            .unwrap();
+        self.contents = contents;
+    }
+}
+
+pub(crate) struct WatchState {
+    config: Config,
+    per_config_context: PerConfigContext,
+    files: HashMap<PathBuf, WatchedFile>,
+}
+
+impl WatchState {
+    #[instrument(level = "debug", skip_all)]
+    pub(crate) fn load(
+        args: &Args,
+        args_to_config: &impl Fn(Args) -> Result<Config, Error>,
+        from_file_run_context_instance_provider_factory: &dyn FromFileRunContextInstanceProviderFactory,
+    ) -> Result<Self, Error> {
+        let config = args_to_config(args.clone())?;
+        let per_config_context = get_per_config_context(&config)?;
+        let mut state = Self {
+            config,
+            per_config_context,
+            files: Default::default(),
+        };
+        state.scan_for_new_files(
+            state.config.paths.clone(),
+            from_file_run_context_instance_provider_factory,
+        )?;
+        Ok(state)
+    }
+
+    // The initial scan goes through tree-sitter-grep (like a normal run) so
+    // that ignore files get respected. Newly-created files go through it too,
+    // but only once they've been checked against ignore files (since
+    // tree-sitter-grep doesn't apply those to paths it's explicitly given)
+    #[instrument(level = "debug", skip_all)]
+    fn scan_for_new_files(
+        &mut self,
+        paths: Vec<PathBuf>,
+        from_file_run_context_instance_provider_factory: &dyn FromFileRunContextInstanceProviderFactory,
+    ) -> Result<(), Error> {
+        let setup_error: Mutex<Option<Error>> = Default::default();
+        let new_files: Mutex<Vec<(PathBuf, WatchedFile)>> = Default::default();
+        tree_sitter_grep::run_with_single_per_file_callback(
+            get_tree_sitter_grep_args(
+                self.per_config_context.borrow_aggregated_queries(),
+                paths,
+                None,
+            ),
+            |dir_entry, supported_language_language, file_contents, tree, _| {
+                let path = dir_entry.path();
+                let Ok(key) = path.canonicalize() else {
+                    return;
+                };
+                if self.files.contains_key(&key) {
+                    return;
+                }
+                let mut watched_file = WatchedFile {
+                    path: path.to_owned(),
+                    contents: file_contents.to_owned(),
+                    tree: tree.clone(),
+                    supported_language_language,
+                    violations: Default::default(),
+                };
+                match lint_file(
+                    &self.config,
+                    &self.per_config_context,
+                    from_file_run_context_instance_provider_factory,
+                    &watched_file,
+                ) {
+                    Ok(violations) => {
+                        watched_file.violations = violations;
+                        new_files.lock().unwrap().push((key, watched_file));
+                    }
+                    Err(error) => {
+                        setup_error.lock().unwrap().get_or_insert(error);
+                    }
+                }
+            },
+        )
+        .map_err(|error| Error::FileSearch(error.to_string()))?;
+        if let Some(error) = setup_error.into_inner().unwrap() {
+            return Err(error);
+        }
+        self.files.extend(new_files.into_inner().unwrap());
+        Ok(())
+    }
+
+    // Expects canonicalized paths
+    #[instrument(level = "debug", skip_all)]
+    pub(crate) fn update(
+        &mut self,
+        changed_paths: HashSet<PathBuf>,
+        from_file_run_context_instance_provider_factory: &dyn FromFileRunContextInstanceProviderFactory,
+    ) -> Result<(), Error> {
+        let mut new_paths: HashSet<PathBuf> = Default::default();
+        for path in changed_paths {
+            let Some(watched_file) = self.files.get_mut(&path) else {
+                if path.is_file() && get_supported_language_language_for_path(&path).is_some() {
+                    new_paths.insert(path);
+                }
+                continue;
+            };
+            let Ok(contents) = fs::read(&path) else {
+                debug!(?path, "file went away");
+                self.files.remove(&path);
+                continue;
+            };
+            if contents == watched_file.contents {
+                continue;
+            }
+            watched_file.update_contents(contents);
+            watched_file.violations = lint_file(
+                &self.config,
+                &self.per_config_context,
+                from_file_run_context_instance_provider_factory,
+                watched_file,
+            )?;
+        }
+        if !new_paths.is_empty() {
+            let paths = get_non_ignored_paths(&get_watched_paths(&self.config.paths), new_paths);
+            if !paths.is_empty() {
+                self.scan_for_new_files(paths, from_file_run_context_instance_provider_factory)?;
+            }
+        }
+        Ok(())
+    }
+
+    #[cfg(test)]
+    pub(crate) fn get_violations(&self, path: &Path) -> Option<&[ViolationWithContext]> {
+        self.files
+            .get(path)
+            .map(|watched_file| &*watched_file.violations)
+    }
+
+    fn output(&self) {
+        let mut violations = self
+            .files
+            .values()
+            .flat_map(|watched_file| watched_file.violations.iter().cloned())
+            .collect::<Vec<_>>();
+        violations.sort_by(|a, b| {
+            a.path
+                .cmp(&b.path)
+                .then(a.range.start_byte.cmp(&b.range.start_byte))
+        });
+        let violations = match apply_baseline(&self.config, violations) {
+            Ok((violations, _)) => filter_for_quiet(violations, &self.config),
+            Err(error) => {
+                print_error(&error);
+                return;
+            }
+        };
+        match format_violations(&violations, &self.config, |path| {
+            self.files
+                .values()
+                .find(|watched_file| watched_file.path == path)
+                .map(|watched_file| watched_file.contents.clone())
+        }) {
+            Ok(output) => print!("{output}"),
+            Err(error) => print_error(&error),
+        }
+        println!("\nWatching for changes...");
+    }
+}
+
+fn get_watched_paths(paths: &[PathBuf]) -> Vec<PathBuf> {
+    if paths.is_empty() {
+        vec![PathBuf::from(".")]
+    } else {
+        paths.to_owned()
+    }
+}
+
+// Walks only as far as it takes to reach the new paths, so that the same
+// ignore files get applied as for a full scan. Returns them relative to the
+// watched path they're under (like a full scan would)
+fn get_non_ignored_paths(watched_paths: &[PathBuf], new_paths: HashSet<PathBuf>) -> Vec<PathBuf> {
+    let new_paths = Arc::new(new_paths);
+    let mut non_ignored_paths: Vec<PathBuf> = Default::default();
+    for watched_path in watched_paths {
+        let Ok(canonical_watched_path) = watched_path.canonicalize() else {
+            continue;
+        };
+        non_ignored_paths.extend(
+            WalkBuilder::new(&canonical_watched_path)
+                .filter_entry({
+                    let new_paths = new_paths.clone();
+                    move |dir_entry| {
+                        new_paths
+                            .iter()
+                            .any(|new_path| new_path.starts_with(dir_entry.path()))
+                    }
+                })
+                .build()
+                .filter_map(Result::ok)
+                .filter(|dir_entry| new_paths.contains(dir_entry.path()))
+                .map(|dir_entry| {
+                    match dir_entry
+                        .path()
+                        .strip_prefix(&canonical_watched_path)
+                        .unwrap()
+                    {
+                        // the watched path is itself a (re-created) file
+                        relative_path if relative_path.as_os_str().is_empty() => {
+                            watched_path.clone()
+                        }
+                        relative_path => watched_path.join(relative_path),
+                    }
+                }),
+        );
+    }
+    non_ignored_paths
+}
+
+fn lint_file(
+    config: &Config,
+    top_level_per_config_context: &PerConfigContext,
+    from_file_run_context_instance_provider_factory: &dyn FromFileRunContextInstanceProviderFactory,
+    watched_file: &WatchedFile,
+) -> Result<Vec<ViolationWithContext>, Error> {
+    let cascaded_config = config.get_cascaded_config_for_path(&watched_file.path)?;
+    let per_config_context = cascaded_config
+        .as_ref()
+        .map_or(top_level_per_config_context, |cascaded_config| {
+            &cascaded_config.per_config_context
+        });
+    if !per_config_context
+        .borrow_aggregated_queries()
+        .per_language
+        .contains_key(&watched_file.supported_language_language)
+    {
+        return Ok(Default::default());
+    }
+    Ok(run_for_slice(
+        &*watched_file.contents,
+        Some(watched_file.tree.clone()),
+        &watched_file.path,
+        config,
+        watched_file.supported_language_language,
+        from_file_run_context_instance_provider_factory,
+        Some(per_config_context),
+    )?
+    .violations)
+}
+
+pub fn run_watch(
+    args: Args,
+    args_to_config: impl Fn(Args) -> Result<Config, Error>,
+    from_file_run_context_instance_provider_factory: &dyn FromFileRunContextInstanceProviderFactory,
+) -> ExitStatus {
+    match watch(
+        args,
+        args_to_config,
+        from_file_run_context_instance_provider_factory,
+    ) {
+        Ok(()) => ExitStatus::Ok,
+        Err(error) => {
+            print_error(&error);
+            ExitStatus::SetupError
+        }
+    }
+}
+
+fn watch(
+    mut args: Args,
+    args_to_config: impl Fn(Args) -> Result<Config, Error>,
+    from_file_run_context_instance_provider_factory: &dyn FromFileRunContextInstanceProviderFactory,
+) -> Result<(), Error> {
+    let config_file_path = match args.config.as_ref() {
+        Some(config_file_path) => canonicalize_config_file_path(config_file_path)?,
+        None => find_config_file()?,
+    };
+    args.config = Some(config_file_path.clone());
+
+    let (sender, receiver) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender).map_err(Error::Watch)?;
+    // editors tend to save by renaming over the original file, so watch the
+    // containing directory rather than the config file itself
+    watcher
+        .watch(
+            config_file_path.parent().unwrap(),
+            RecursiveMode::NonRecursive,
+        )
+        .map_err(Error::Watch)?;
+    for path in &get_watched_paths(&args.paths) {
+        // so that event paths (even for deleted files) match the
+        // canonicalized keys of `WatchState::files`
+        let path = path.canonicalize().unwrap_or_else(|_| path.clone());
+        watcher
+            .watch(&path, RecursiveMode::Recursive)
+            .map_err(Error::Watch)?;
+    }
+
+    let load = || {
+        WatchState::load(
+            &args,
+            &args_to_config,
+            from_file_run_context_instance_provider_factory,
+        )
+        .map_err(|error| print_error(&error))
+        .ok()
+    };
+    // the plugins are compiled into this binary, so picking up changes to
+    // them means rebuilding
+    let get_plugins = || {
+        load_config_file(Some(&config_file_path))
+            .ok()
+            .map(|parsed_config_file| parsed_config_file.content.plugins)
+    };
+    let mut plugins = get_plugins();
+    print!("{CLEAR_SCREEN}");
+    let mut state = load();
+    if let Some(state) = state.as_ref() {
+        state.output();
+    }
+    while let Ok(event) = receiver.recv() {
+        let mut changed_paths: HashSet<PathBuf> = Default::default();
+        let mut add_event_paths = |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event {
+                changed_paths.extend(
+                    event
+                        .paths
+                        .into_iter()
+                        .map(|path| path.canonicalize().unwrap_or(path)),
+                );
+            }
+        };
+        add_event_paths(event);
+        while let Ok(event) = receiver.recv_timeout(DEBOUNCE_INTERVAL) {
+            add_event_paths(event);
+        }
+        let is_config_change = changed_paths
+            .iter()
+            .any(|path| is_config_file_path(path, &config_file_path));
+        match state.as_mut() {
+            Some(existing_state) if !is_config_change => {
+                let had_changes = changed_paths.iter().any(|path| {
+                    existing_state.files.contains_key(path)
+                        || get_supported_language_language_for_path(path).is_some()
+                });
+                if !had_changes {
+                    continue;
+                }
+                print!("{CLEAR_SCREEN}");
+                if let Err(error) = existing_state.update(
+                    changed_paths,
+                    from_file_run_context_instance_provider_factory,
+                ) {
+                    print_error(&error);
+                    continue;
+                }
+                existing_state.output();
+            }
+            _ => {
+                debug!("reloading config");
+                print!("{CLEAR_SCREEN}");
+                state = load();
+                if let Some(state) = state.as_ref() {
+                    state.output();
+                }
+                let new_plugins = get_plugins();
+                if new_plugins.is_some() && new_plugins != plugins {
+                    eprintln!("Plugins changed, restart to pick them up");
+                    plugins = new_plugins;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn is_config_file_path(path: &Path, config_file_path: &Path) -> bool {
+    path == config_file_path
+        || path
+            .file_name()
+            .is_some_and(|file_name| file_name == CONFIG_FILENAME)
+}
+
+// Tree-sitter wants to be told what changed, which for a file that changed on
+// disk means diffing the old and new contents
+pub(crate) fn get_input_edit(old_contents: &[u8], new_contents: &[u8]) -> InputEdit {
+    let start_byte = old_contents
+        .iter()
+        .zip(new_contents)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let common_suffix_len = old_contents[start_byte..]
+        .iter()
+        .rev()
+        .zip(new_contents[start_byte..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let old_end_byte = old_contents.len() - common_suffix_len;
+    let new_end_byte = new_contents.len() - common_suffix_len;
+    InputEdit {
+        start_byte,
+        old_end_byte,
+        new_end_byte,
+        start_position: byte_offset_to_point(old_contents, start_byte),
+        old_end_position: byte_offset_to_point(old_contents, old_end_byte),
+        new_end_position: byte_offset_to_point(new_contents, new_end_byte),
+    }
+}
+
+fn byte_offset_to_point(contents: &[u8], byte_offset: usize) -> Point {
+    let preceding = &contents[..byte_offset];
+    let row = preceding.iter().filter(|&&byte| byte == b'\n').count();
+    let column = match preceding.iter().rposition(|&byte| byte == b'\n') {
+        Some(newline_offset) => byte_offset - newline_offset - 1,
+        None => byte_offset,
+    };
+    Point { row, column }
+}